@@ -1,86 +1,257 @@
-use criterion::{Criterion, criterion_group, criterion_main};
-use crossbeam::queue::SegQueue;
-use queue::AtomicQueue;
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, Criterion, criterion_group, criterion_main};
+use crossbeam::queue as cb;
+use queue::{ArrayQueue, AtomicQueue, ConcurrentQueue, Hazard, SegQueue, first, spsc};
 use std::sync::Arc;
 use std::thread;
 
-fn bench_single_thread(c: &mut Criterion) {
-    let mut group = c.benchmark_group("single_thread");
+fn single_thread<Q: ConcurrentQueue<usize>>(q: &Q, iters: usize) {
+    for i in 0..iters {
+        let _ = q.push(i);
+        q.pop();
+    }
+}
 
-    group.bench_function("AtomicQueue single-thread", |b| {
-        b.iter(|| {
-            let q = AtomicQueue::new();
-            for i in 0..1_000_0 {
-                q.enqueue(i);
-                q.dequeue();
-            }
-        });
+fn run_single<Q: ConcurrentQueue<usize>>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    make: fn() -> Q,
+) {
+    group.bench_function(format!("{} single-thread", name), |b| {
+        b.iter(|| single_thread(&make(), 10_000));
     });
+}
 
-    group.bench_function("SegQueue single-thread", |b| {
+fn run_multi<Q>(group: &mut BenchmarkGroup<WallTime>, name: &str, make: fn() -> Q, threads: usize)
+where
+    Q: ConcurrentQueue<usize> + Send + Sync + 'static,
+{
+    let iters = 100_000;
+    group.bench_function(format!("{} {} threads", name, threads), |b| {
         b.iter(|| {
-            let q = SegQueue::new();
-            for i in 0..1_000_0 {
-                q.push(i);
-                q.pop();
+            let q = Arc::new(make());
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    let q = q.clone();
+                    thread::spawn(move || single_thread(&*q, iters))
+                })
+                .collect();
+
+            for h in handles {
+                h.join().unwrap();
             }
         });
     });
+}
 
+//every queue under test. bounded ones get enough room for one item per thread
+macro_rules! for_each_queue {
+    ($run:ident, $group:expr $(, $arg:expr)*) => {
+        $run($group, "AtomicQueue", AtomicQueue::new $(, $arg)*);
+        $run($group, "AtomicQueue<Hazard>", AtomicQueue::<_, Hazard>::with_reclaim $(, $arg)*);
+        $run($group, "first::AtomicQueue", first::AtomicQueue::new $(, $arg)*);
+        $run($group, "SegQueue", SegQueue::new $(, $arg)*);
+        $run($group, "ArrayQueue", || ArrayQueue::new(1024) $(, $arg)*);
+        $run($group, "crossbeam SegQueue", cb::SegQueue::new $(, $arg)*);
+        $run($group, "crossbeam ArrayQueue", || cb::ArrayQueue::new(1024) $(, $arg)*);
+    };
+}
+
+fn bench_single_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_thread");
+    for_each_queue!(run_single, &mut group);
     group.finish();
 }
 
 fn bench_multi_thread(c: &mut Criterion) {
     let mut group = c.benchmark_group("multi_thread");
     let thread_counts = [2, 4, 8, 16];
-    let iters = 100_000;
 
     for &threads in &thread_counts {
-        group.bench_function(format!("AtomicQueue {} threads", threads), |b| {
+        for_each_queue!(run_multi, &mut group, threads);
+    }
+
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+    let items = 10_000;
+
+    for &batch in &[16, 64, 256] {
+        group.bench_function(format!("AtomicQueue per-item x{}", batch), |b| {
+            b.iter(|| {
+                let q = AtomicQueue::new();
+                for _ in 0..items / batch {
+                    for i in 0..batch {
+                        q.enqueue(i);
+                    }
+                    for _ in 0..batch {
+                        q.dequeue();
+                    }
+                }
+            });
+        });
+
+        group.bench_function(format!("AtomicQueue batch x{}", batch), |b| {
             b.iter(|| {
-                let q = Arc::new(AtomicQueue::new());
-                let handles: Vec<_> = (0..threads)
-                    .map(|_| {
-                        let q = q.clone();
-                        thread::spawn(move || {
-                            for i in 0..iters {
+                let q = AtomicQueue::new();
+                let mut buf = Vec::with_capacity(batch);
+                for _ in 0..items / batch {
+                    q.enqueue_batch(0..batch);
+                    q.dequeue_batch(&mut buf, batch);
+                    buf.clear();
+                }
+            });
+        });
+    }
+
+    let threads = 4;
+    let iters = 100_000;
+    let batch = 64;
+
+    group.bench_function(format!("AtomicQueue per-item {} threads", threads), |b| {
+        b.iter(|| {
+            let q = Arc::new(AtomicQueue::new());
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    let q = q.clone();
+                    thread::spawn(move || {
+                        for _ in 0..iters / batch {
+                            for i in 0..batch {
                                 q.enqueue(i);
+                            }
+                            for _ in 0..batch {
                                 q.dequeue();
                             }
-                        })
+                        }
                     })
-                    .collect();
+                })
+                .collect();
 
-                for h in handles {
-                    h.join().unwrap();
-                }
-            });
+            for h in handles {
+                h.join().unwrap();
+            }
         });
+    });
 
-        group.bench_function(format!("SegQueue {} threads", threads), |b| {
-            b.iter(|| {
-                let q = Arc::new(SegQueue::new());
-                let handles: Vec<_> = (0..threads)
-                    .map(|_| {
-                        let q = q.clone();
-                        thread::spawn(move || {
-                            for i in 0..iters {
-                                q.push(i);
-                                q.pop();
-                            }
-                        })
+    group.bench_function(format!("AtomicQueue batch {} threads", threads), |b| {
+        b.iter(|| {
+            let q = Arc::new(AtomicQueue::new());
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    let q = q.clone();
+                    thread::spawn(move || {
+                        let mut buf = Vec::with_capacity(batch);
+                        for _ in 0..iters / batch {
+                            q.enqueue_batch(0..batch);
+                            q.dequeue_batch(&mut buf, batch);
+                            buf.clear();
+                        }
                     })
-                    .collect();
+                })
+                .collect();
+
+            for h in handles {
+                h.join().unwrap();
+            }
+        });
+    });
+
+    group.finish();
+}
 
-                for h in handles {
-                    h.join().unwrap();
+//one producer thread and one consumer thread, the shape spsc::ring is made for
+fn pipeline<Q>(make: fn() -> Q, items: usize)
+where
+    Q: ConcurrentQueue<usize> + Send + Sync + 'static,
+{
+    let q = Arc::new(make());
+    let producer = {
+        let q = q.clone();
+        thread::spawn(move || {
+            for i in 0..items {
+                let mut v = i;
+                while let Err(back) = q.push(v) {
+                    v = back;
+                    std::hint::spin_loop();
+                }
+            }
+        })
+    };
+    let mut got = 0;
+    while got < items {
+        if q.pop().is_some() {
+            got += 1;
+        }
+    }
+    producer.join().unwrap();
+}
+
+fn run_pipeline<Q>(group: &mut BenchmarkGroup<WallTime>, name: &str, make: fn() -> Q, items: usize)
+where
+    Q: ConcurrentQueue<usize> + Send + Sync + 'static,
+{
+    group.bench_function(format!("{} pipeline", name), |b| {
+        b.iter(|| pipeline(make, items));
+    });
+}
+
+fn bench_spsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc");
+    let items = 100_000;
+
+    group.bench_function("spsc::ring pipeline", |b| {
+        b.iter(|| {
+            let (mut tx, mut rx) = spsc::ring(1024);
+            let producer = thread::spawn(move || {
+                for i in 0..items {
+                    let mut v = i;
+                    while let Err(back) = tx.push(v) {
+                        v = back;
+                        std::hint::spin_loop();
+                    }
                 }
             });
+            let mut got = 0;
+            while got < items {
+                if rx.pop().is_some() {
+                    got += 1;
+                }
+            }
+            producer.join().unwrap();
         });
-    }
+    });
+
+    group.bench_function("spsc::ring chunked pipeline", |b| {
+        b.iter(|| {
+            let (mut tx, mut rx) = spsc::ring(1024);
+            let producer = thread::spawn(move || {
+                let chunk: Vec<usize> = (0..64).collect();
+                let mut sent = 0;
+                while sent < items {
+                    let n = (items - sent).min(chunk.len());
+                    sent += tx.write_chunk(&chunk[..n]);
+                }
+            });
+            let mut buf = [0; 64];
+            let mut got = 0;
+            while got < items {
+                got += rx.read_chunk(&mut buf);
+            }
+            producer.join().unwrap();
+        });
+    });
 
+    for_each_queue!(run_pipeline, &mut group, items);
     group.finish();
 }
 
-criterion_group!(benches, bench_single_thread, bench_multi_thread);
+criterion_group!(
+    benches,
+    bench_single_thread,
+    bench_multi_thread,
+    bench_batch,
+    bench_spsc
+);
 criterion_main!(benches);
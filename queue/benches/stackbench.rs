@@ -0,0 +1,106 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use queue::Stack;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn bench_single_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stack_single_thread");
+
+    group.bench_function("Stack single-thread", |b| {
+        b.iter(|| {
+            let s = Stack::new();
+            for i in 0..10_000 {
+                s.push(i);
+                s.pop();
+            }
+        });
+    });
+
+    group.bench_function("Mutex<Vec> single-thread", |b| {
+        b.iter(|| {
+            let s = Mutex::new(Vec::new());
+            for i in 0..10_000 {
+                s.lock().unwrap().push(i);
+                s.lock().unwrap().pop();
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn bench_multi_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stack_multi_thread");
+    let thread_counts = [2, 4, 8, 16];
+    let iters = 100_000;
+
+    for &threads in &thread_counts {
+        group.bench_function(format!("Stack {} threads", threads), |b| {
+            b.iter(|| {
+                let s = Arc::new(Stack::new());
+                let handles: Vec<_> = (0..threads)
+                    .map(|_| {
+                        let s = s.clone();
+                        thread::spawn(move || {
+                            for i in 0..iters {
+                                s.push(i);
+                                s.pop();
+                            }
+                        })
+                    })
+                    .collect();
+
+                for h in handles {
+                    h.join().unwrap();
+                }
+            });
+        });
+
+        group.bench_function(format!("Stack+elimination {} threads", threads), |b| {
+            b.iter(|| {
+                let s = Arc::new(Stack::with_elimination((threads / 2).max(1)));
+                let handles: Vec<_> = (0..threads)
+                    .map(|_| {
+                        let s = s.clone();
+                        thread::spawn(move || {
+                            for i in 0..iters {
+                                s.push(i);
+                                s.pop();
+                            }
+                        })
+                    })
+                    .collect();
+
+                for h in handles {
+                    h.join().unwrap();
+                }
+            });
+        });
+
+        group.bench_function(format!("Mutex<Vec> {} threads", threads), |b| {
+            b.iter(|| {
+                let s = Arc::new(Mutex::new(Vec::new()));
+                let handles: Vec<_> = (0..threads)
+                    .map(|_| {
+                        let s = s.clone();
+                        thread::spawn(move || {
+                            for i in 0..iters {
+                                s.lock().unwrap().push(i);
+                                s.lock().unwrap().pop();
+                            }
+                        })
+                    })
+                    .collect();
+
+                for h in handles {
+                    h.join().unwrap();
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_single_thread, bench_multi_thread);
+criterion_main!(benches);
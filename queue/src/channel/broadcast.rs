@@ -0,0 +1,264 @@
+use super::SendError;
+use super::waiter::{Waiters, wait_until};
+use core::fmt;
+use std::error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+//`pos` is the sequence number of the message in the slot, a receiver
+//compares it with its own cursor to tell "not written yet" from "overwritten"
+struct Slot<T> {
+    pos: u64,
+    msg: Option<T>,
+}
+
+struct Shared<T> {
+    slots: Box<[RwLock<Slot<T>>]>,
+    //sequence number of the next message, senders serialize on it
+    tail: Mutex<u64>,
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+    receiving: Waiters,
+}
+
+impl<T> Shared<T> {
+    fn cap(&self) -> u64 {
+        self.slots.len() as u64
+    }
+
+    fn slot(&self, pos: u64) -> &RwLock<Slot<T>> {
+        &self.slots[(pos % self.cap()) as usize]
+    }
+}
+
+//every message goes to every receiver. the ring never blocks senders, a
+//receiver that falls more than `cap` messages behind skips ahead and is told how many it lost
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    //sequence number of the next message this receiver reads
+    next: u64,
+}
+
+pub fn channel<T: Clone>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(cap > 0, "capacity must be non-zero");
+    let shared = Arc::new(Shared {
+        slots: (0..cap)
+            .map(|_| RwLock::new(Slot { pos: 0, msg: None }))
+            .collect(),
+        tail: Mutex::new(0),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+        receiving: Waiters::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, next: 0 },
+    )
+}
+
+impl<T: Clone> Sender<T> {
+    //fails only if there is no receiver left to see the message
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        if self.shared.receivers.load(Ordering::Acquire) == 0 {
+            return Err(SendError(msg));
+        }
+
+        let mut tail = self.shared.tail.lock().unwrap();
+        let pos = *tail;
+        {
+            let mut slot = self.shared.slot(pos).write().unwrap();
+            slot.pos = pos;
+            slot.msg = Some(msg);
+        }
+        *tail += 1;
+        drop(tail);
+
+        self.shared.receiving.notify_all();
+        Ok(())
+    }
+
+    //a receiver that only sees messages sent from now on
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared.receivers.fetch_add(1, Ordering::Relaxed);
+        let next = *self.shared.tail.lock().unwrap();
+        Receiver {
+            shared: self.shared.clone(),
+            next,
+        }
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receivers.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        {
+            let slot = self.shared.slot(self.next).read().unwrap();
+            if slot.msg.is_some() && slot.pos == self.next {
+                self.next += 1;
+                return Ok(slot.msg.clone().unwrap());
+            }
+        }
+
+        let tail = *self.shared.tail.lock().unwrap();
+        if tail <= self.next {
+            return Err(if self.shared.senders.load(Ordering::Acquire) == 0 {
+                TryRecvError::Disconnected
+            } else {
+                TryRecvError::Empty
+            });
+        }
+        if tail - self.next > self.shared.cap() {
+            //overrun, resume at the oldest message still in the ring
+            let oldest = tail - self.shared.cap();
+            let missed = oldest - self.next;
+            self.next = oldest;
+            return Err(TryRecvError::Lagged(missed));
+        }
+        //the slot was written between our two looks
+        self.try_recv()
+    }
+
+    fn recv_deadline(&mut self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let shared = self.shared.clone();
+        wait_until(&shared.receiving, deadline, || match self.try_recv() {
+            Ok(msg) => Some(Ok(msg)),
+            Err(TryRecvError::Lagged(n)) => Some(Err(RecvTimeoutError::Lagged(n))),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvTimeoutError::Disconnected)),
+            Err(TryRecvError::Empty) => None,
+        })
+        .unwrap_or(Err(RecvTimeoutError::Timeout))
+    }
+
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        self.recv_deadline(None).map_err(|e| match e {
+            RecvTimeoutError::Lagged(n) => RecvError::Lagged(n),
+            RecvTimeoutError::Disconnected => RecvError::Disconnected,
+            RecvTimeoutError::Timeout => unreachable!(),
+        })
+    }
+
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now().checked_add(timeout))
+    }
+
+    //another receiver starting at the newest message, like `Sender::subscribe`
+    pub fn resubscribe(&self) -> Receiver<T> {
+        self.shared.receivers.fetch_add(1, Ordering::Relaxed);
+        let next = *self.shared.tail.lock().unwrap();
+        Receiver {
+            shared: self.shared.clone(),
+            next,
+        }
+    }
+
+    //messages this receiver hasn't read yet, lost ones included
+    pub fn len(&self) -> usize {
+        let tail = *self.shared.tail.lock().unwrap();
+        tail.saturating_sub(self.next) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.receiving.notify_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receivers.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvError {
+    //fell behind and skipped this many messages, the next recv continues with the oldest one left
+    Lagged(u64),
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    Empty,
+    Lagged(u64),
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Lagged(u64),
+    Disconnected,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+            RecvError::Disconnected => f.pad("receiving on an empty and disconnected channel"),
+        }
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.pad("receiving on an empty channel"),
+            TryRecvError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+            TryRecvError::Disconnected => f.pad("receiving on an empty and disconnected channel"),
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.pad("timed out waiting on receive operation"),
+            RecvTimeoutError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+            RecvTimeoutError::Disconnected => {
+                f.pad("receiving on an empty and disconnected channel")
+            }
+        }
+    }
+}
+
+impl error::Error for RecvError {}
+impl error::Error for TryRecvError {}
+impl error::Error for RecvTimeoutError {}
@@ -0,0 +1,297 @@
+use super::waiter::{TaskEntry, poll_until};
+use super::{Flavor, Receiver, RecvError, SendError, Sender, Shared, TryRecvError, TrySendError};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+//registration of a pending async receive, re-armed when a wakeup took it
+struct RecvState {
+    entry: Option<TaskEntry>,
+}
+
+impl RecvState {
+    fn new() -> Self {
+        RecvState { entry: None }
+    }
+
+    fn attempt<T>(shared: &Shared<T>) -> Option<Result<T, RecvError>> {
+        match shared.try_recv() {
+            Ok(msg) => Some(Ok(msg)),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvError::Disconnected)),
+            Err(TryRecvError::Empty) => None,
+        }
+    }
+
+    fn poll_recv<T>(
+        &mut self,
+        shared: &Shared<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, RecvError>> {
+        poll_until(&shared.receiving, &mut self.entry, cx, || Self::attempt(shared))
+    }
+
+    fn cancel<T>(&mut self, shared: &Shared<T>) {
+        //pass on a wakeup we were handed but will never act on
+        if let Some(entry) = self.entry.take()
+            && !shared.receiving.unregister_task(entry)
+        {
+            shared.receiving.notify_one();
+        }
+    }
+}
+
+//a message on its way out. for rendezvous channels `ticket` is set once
+//the message sits in the slot and we are waiting for a receiver to take it
+struct SendState<T> {
+    msg: Option<T>,
+    ticket: Option<u64>,
+    entry: Option<TaskEntry>,
+}
+
+impl<T> SendState<T> {
+    fn new(msg: Option<T>) -> Self {
+        SendState {
+            msg,
+            ticket: None,
+            entry: None,
+        }
+    }
+
+    fn attempt(&mut self, shared: &Shared<T>) -> Option<Result<(), SendError<T>>> {
+        if let (Some(ticket), Flavor::Zero(slot)) = (self.ticket, &shared.flavor) {
+            if slot.is_taken(ticket) {
+                self.ticket = None;
+                return Some(Ok(()));
+            }
+            if shared.receivers_gone() {
+                self.ticket = None;
+                return Some(
+                    shared
+                        .reclaim(slot, ticket)
+                        .map_or(Ok(()), |m| Err(SendError(m))),
+                );
+            }
+            return None;
+        }
+
+        let msg = match self.msg.take() {
+            Some(msg) => msg,
+            None => return Some(Ok(())),
+        };
+        if let Flavor::Zero(slot) = &shared.flavor {
+            if shared.receivers_gone() {
+                return Some(Err(SendError(msg)));
+            }
+            return match slot.offer(msg) {
+                Ok(ticket) => {
+                    self.ticket = Some(ticket);
+                    shared.receiving.notify_one();
+                    self.attempt(shared)
+                }
+                Err(msg) => {
+                    self.msg = Some(msg);
+                    None
+                }
+            };
+        }
+
+        match shared.try_send(msg) {
+            Ok(()) => Some(Ok(())),
+            Err(TrySendError::Disconnected(msg)) => Some(Err(SendError(msg))),
+            Err(TrySendError::Full(msg)) => {
+                self.msg = Some(msg);
+                None
+            }
+        }
+    }
+
+    fn poll_send(
+        &mut self,
+        shared: &Shared<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let mut entry = self.entry.take();
+        let res = poll_until(&shared.sending, &mut entry, cx, || self.attempt(shared));
+        self.entry = entry;
+        res
+    }
+
+    fn cancel(&mut self, shared: &Shared<T>) {
+        if let Some(entry) = self.entry.take()
+            && !shared.sending.unregister_task(entry)
+        {
+            shared.sending.notify_one();
+        }
+        //a dropped rendezvous send takes its message back out of the slot
+        if let (Some(ticket), Flavor::Zero(slot)) = (self.ticket.take(), &shared.flavor) {
+            shared.reclaim(slot, ticket);
+        }
+    }
+}
+
+pub struct RecvFuture<'a, T> {
+    receiver: &'a Receiver<T>,
+    state: RecvState,
+}
+
+pub struct SendFuture<'a, T> {
+    sender: &'a Sender<T>,
+    state: SendState<T>,
+}
+
+//the message is never pinned, it is moved into the channel by value
+impl<T> Unpin for SendFuture<'_, T> {}
+
+impl<T> Receiver<T> {
+    pub fn recv_async(&self) -> RecvFuture<'_, T> {
+        RecvFuture {
+            receiver: self,
+            state: RecvState::new(),
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn send_async(&self, msg: T) -> SendFuture<'_, T> {
+        SendFuture {
+            sender: self,
+            state: SendState::new(Some(msg)),
+        }
+    }
+}
+
+impl<T> Future for RecvFuture<'_, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.state.poll_recv(&this.receiver.shared, cx)
+    }
+}
+
+impl<T> Future for SendFuture<'_, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.state.poll_send(&this.sender.shared, cx)
+    }
+}
+
+impl<T> Drop for RecvFuture<'_, T> {
+    fn drop(&mut self) {
+        self.state.cancel(&self.receiver.shared);
+    }
+}
+
+impl<T> Drop for SendFuture<'_, T> {
+    fn drop(&mut self) {
+        self.state.cancel(&self.sender.shared);
+    }
+}
+
+#[cfg(feature = "futures")]
+pub use adapters::{RecvStream, SendSink};
+
+#[cfg(feature = "futures")]
+mod adapters {
+    use super::*;
+    use futures_core::Stream;
+    use futures_sink::Sink;
+
+    pub struct RecvStream<T> {
+        receiver: Receiver<T>,
+        state: RecvState,
+    }
+
+    pub struct SendSink<T> {
+        sender: Sender<T>,
+        state: SendState<T>,
+    }
+
+    impl<T> Unpin for SendSink<T> {}
+
+    impl<T> SendState<T> {
+        fn is_idle(&self) -> bool {
+            self.msg.is_none() && self.ticket.is_none()
+        }
+    }
+
+    impl<T> Receiver<T> {
+        pub fn into_stream(self) -> RecvStream<T> {
+            RecvStream {
+                receiver: self,
+                state: RecvState::new(),
+            }
+        }
+
+        pub fn stream(&self) -> RecvStream<T> {
+            self.clone().into_stream()
+        }
+    }
+
+    impl<T> Sender<T> {
+        pub fn into_sink(self) -> SendSink<T> {
+            SendSink {
+                sender: self,
+                state: SendState::new(None),
+            }
+        }
+
+        pub fn sink(&self) -> SendSink<T> {
+            self.clone().into_sink()
+        }
+    }
+
+    impl<T> Stream for RecvStream<T> {
+        type Item = T;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+            let this = &mut *self;
+            this.state
+                .poll_recv(&this.receiver.shared, cx)
+                .map(Result::ok)
+        }
+    }
+
+    impl<T> Sink<T> for SendSink<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.poll_flush(cx)
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            debug_assert!(self.state.is_idle(), "start_send called without poll_ready");
+            self.state = SendState::new(Some(item));
+            Ok(())
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = &mut *self;
+            if this.state.is_idle() {
+                return Poll::Ready(Ok(()));
+            }
+            this.state.poll_send(&this.sender.shared, cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    impl<T> Drop for RecvStream<T> {
+        fn drop(&mut self) {
+            self.state.cancel(&self.receiver.shared);
+        }
+    }
+
+    impl<T> Drop for SendSink<T> {
+        fn drop(&mut self) {
+            self.state.cancel(&self.sender.shared);
+        }
+    }
+}
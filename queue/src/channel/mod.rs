@@ -0,0 +1,490 @@
+pub mod broadcast;
+pub mod oneshot;
+pub mod watch;
+mod future;
+mod select;
+mod waiter;
+mod zero;
+
+use crate::arrayQueue::ArrayQueue;
+use crate::second::AtomicQueue;
+use core::fmt;
+use std::error;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use waiter::{Waiters, wait_until};
+
+pub use future::{RecvFuture, SendFuture};
+pub use select::{Select, SelectTimeoutError, SelectedOperation, TrySelectError};
+#[cfg(feature = "futures")]
+pub use future::{RecvStream, SendSink};
+
+enum Flavor<T> {
+    List(AtomicQueue<T>),
+    Array(ArrayQueue<T>),
+    Zero(zero::Slot<T>),
+}
+
+struct Shared<T> {
+    flavor: Flavor<T>,
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+    receiving: Waiters,
+    sending: Waiters,
+}
+
+impl<T> Shared<T> {
+    fn new(flavor: Flavor<T>) -> Arc<Self> {
+        Arc::new(Shared {
+            flavor,
+            senders: AtomicUsize::new(1),
+            receivers: AtomicUsize::new(1),
+            receiving: Waiters::new(),
+            sending: Waiters::new(),
+        })
+    }
+
+    #[inline(always)]
+    fn senders_gone(&self) -> bool {
+        self.senders.load(Ordering::SeqCst) == 0
+    }
+
+    #[inline(always)]
+    fn receivers_gone(&self) -> bool {
+        self.receivers.load(Ordering::SeqCst) == 0
+    }
+
+    fn pop(&self) -> Option<T> {
+        match &self.flavor {
+            Flavor::List(q) => q.dequeue(),
+            Flavor::Array(q) => {
+                let msg = q.pop()?;
+                self.sending.notify_one();
+                Some(msg)
+            }
+            Flavor::Zero(slot) => {
+                let msg = slot.take()?;
+                //wakes the sender waiting on pickup as well as those waiting for the slot
+                self.sending.notify_all();
+                Some(msg)
+            }
+        }
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        if let Some(msg) = self.pop() {
+            return Ok(msg);
+        }
+        if self.senders_gone() {
+            //the last sender may have pushed right before leaving
+            return self.pop().ok_or(TryRecvError::Disconnected);
+        }
+        if let Flavor::Zero(_) = &self.flavor {
+            //a receiver showing up makes rendezvous sends ready for selectors
+            self.sending.notify_all();
+        }
+        Err(TryRecvError::Empty)
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        wait_until(&self.receiving, deadline, || match self.try_recv() {
+            Ok(msg) => Some(Ok(msg)),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvTimeoutError::Disconnected)),
+            Err(TryRecvError::Empty) => None,
+        })
+        .unwrap_or(Err(RecvTimeoutError::Timeout))
+    }
+
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.receivers_gone() {
+            return Err(TrySendError::Disconnected(msg));
+        }
+        match &self.flavor {
+            Flavor::List(q) => q.enqueue(msg),
+            Flavor::Array(q) => q.push(msg).map_err(TrySendError::Full)?,
+            //without a blocked receiver there is nobody to rendezvous with
+            Flavor::Zero(slot) => {
+                if self.receiving.is_empty() {
+                    return Err(TrySendError::Full(msg));
+                }
+                slot.offer(msg).map_err(TrySendError::Full)?;
+            }
+        }
+        self.receiving.notify_one();
+        Ok(())
+    }
+
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        if let Flavor::Zero(slot) = &self.flavor {
+            return self.send_zero(slot, msg, deadline);
+        }
+
+        let mut msg = Some(msg);
+        let res = wait_until(&self.sending, deadline, || {
+            match self.try_send(msg.take().unwrap()) {
+                Ok(()) => Some(Ok(())),
+                Err(TrySendError::Disconnected(m)) => Some(Err(SendTimeoutError::Disconnected(m))),
+                Err(TrySendError::Full(m)) => {
+                    msg = Some(m);
+                    None
+                }
+            }
+        });
+        res.unwrap_or_else(|| Err(SendTimeoutError::Timeout(msg.take().unwrap())))
+    }
+
+    //places `msg` in the slot, then blocks until a receiver has taken it
+    fn send_zero(
+        &self,
+        slot: &zero::Slot<T>,
+        msg: T,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let mut msg = Some(msg);
+        let offered = wait_until(&self.sending, deadline, || {
+            if self.receivers_gone() {
+                return Some(Err(SendTimeoutError::Disconnected(msg.take().unwrap())));
+            }
+            match slot.offer(msg.take().unwrap()) {
+                Ok(ticket) => Some(Ok(ticket)),
+                Err(m) => {
+                    msg = Some(m);
+                    None
+                }
+            }
+        });
+        let ticket = match offered {
+            Some(res) => res?,
+            None => return Err(SendTimeoutError::Timeout(msg.take().unwrap())),
+        };
+        self.receiving.notify_one();
+
+        let taken = wait_until(&self.sending, deadline, || {
+            if slot.is_taken(ticket) {
+                Some(true)
+            } else if self.receivers_gone() {
+                Some(false)
+            } else {
+                None
+            }
+        });
+        if taken == Some(true) {
+            return Ok(());
+        }
+        match self.reclaim(slot, ticket) {
+            Some(m) if self.receivers_gone() => Err(SendTimeoutError::Disconnected(m)),
+            Some(m) => Err(SendTimeoutError::Timeout(m)),
+            //a receiver got it between the last check and the reclaim
+            None => Ok(()),
+        }
+    }
+
+    fn reclaim(&self, slot: &zero::Slot<T>, ticket: u64) -> Option<T> {
+        let msg = slot.reclaim(ticket)?;
+        //the slot is free again for senders still waiting to offer
+        self.sending.notify_all();
+        Some(msg)
+    }
+
+    fn is_empty(&self) -> bool {
+        match &self.flavor {
+            Flavor::List(q) => q.is_empty(),
+            Flavor::Array(q) => q.is_empty(),
+            Flavor::Zero(slot) => !slot.is_full(),
+        }
+    }
+
+    fn recv_ready(&self) -> bool {
+        !self.is_empty() || self.senders_gone()
+    }
+
+    fn send_ready(&self) -> bool {
+        if self.receivers_gone() {
+            return true;
+        }
+        match &self.flavor {
+            Flavor::List(_) => true,
+            Flavor::Array(q) => !q.is_full(),
+            Flavor::Zero(slot) => !slot.is_full() && !self.receiving.is_empty(),
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        match &self.flavor {
+            Flavor::List(_) => None,
+            Flavor::Array(q) => Some(q.capacity()),
+            Flavor::Zero(_) => Some(0),
+        }
+    }
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+fn pair<T>(shared: Arc<Shared<T>>) -> (Sender<T>, Receiver<T>) {
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    pair(Shared::new(Flavor::List(AtomicQueue::new())))
+}
+
+//`send` blocks while `cap` messages are buffered.
+//with `cap == 0` every send waits until a receiver takes the message
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    if cap == 0 {
+        pair(Shared::new(Flavor::Zero(zero::Slot::new())))
+    } else {
+        pair(Shared::new(Flavor::Array(ArrayQueue::new(cap))))
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.shared
+            .send(msg, None)
+            .map_err(|e| SendError(e.into_inner()))
+    }
+
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.shared.try_send(msg)
+    }
+
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.shared.send(msg, Instant::now().checked_add(timeout))
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity()
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.receivers_gone()
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.shared.try_recv()
+    }
+
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.shared.recv(None).map_err(|_| RecvError::Disconnected)
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.shared.recv(Instant::now().checked_add(timeout))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shared.is_empty()
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity()
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.senders_gone()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.receivers.fetch_add(1, Ordering::Relaxed);
+        Receiver {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.receiving.notify_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if self.shared.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.sending.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> SendError<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("sending on a disconnected channel")
+    }
+}
+
+impl<T> error::Error for SendError<T> {}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendTimeoutError<T> {
+    Timeout(T),
+    Disconnected(T),
+}
+
+impl<T> TrySendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(msg) | TrySendError::Disconnected(msg) => msg,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+}
+
+impl<T> SendTimeoutError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            SendTimeoutError::Timeout(msg) | SendTimeoutError::Disconnected(msg) => msg,
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, SendTimeoutError::Timeout(_))
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.pad("Full(..)"),
+            TrySendError::Disconnected(_) => f.pad("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.pad("Timeout(..)"),
+            SendTimeoutError::Disconnected(_) => f.pad("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.pad("sending on a full channel"),
+            TrySendError::Disconnected(_) => f.pad("sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.pad("timed out waiting on send operation"),
+            SendTimeoutError::Disconnected(_) => f.pad("sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T> error::Error for TrySendError<T> {}
+impl<T> error::Error for SendTimeoutError<T> {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvError {
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("receiving on an empty and disconnected channel")
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.pad("receiving on an empty channel"),
+            TryRecvError::Disconnected => f.pad("receiving on an empty and disconnected channel"),
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.pad("timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => f.pad("channel is empty and disconnected"),
+        }
+    }
+}
+
+impl error::Error for RecvError {}
+impl error::Error for TryRecvError {}
+impl error::Error for RecvTimeoutError {}
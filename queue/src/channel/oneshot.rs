@@ -0,0 +1,153 @@
+use super::waiter::{TaskEntry, Waiters, poll_until, wait_until};
+use super::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use core::fmt;
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+enum Slot<T> {
+    Empty,
+    Full(T),
+    Taken,
+    //the sender went away without sending
+    Canceled,
+}
+
+struct Shared<T> {
+    slot: Mutex<Slot<T>>,
+    receiver_gone: AtomicBool,
+    receiving: Waiters,
+}
+
+//carries exactly one value. the receiver is also a future resolving to it
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    //waker registration of a pending poll
+    entry: Option<TaskEntry>,
+}
+
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        slot: Mutex::new(Slot::Empty),
+        receiver_gone: AtomicBool::new(false),
+        receiving: Waiters::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, entry: None },
+    )
+}
+
+impl<T> Sender<T> {
+    //hands the value back if the receiver is already gone
+    pub fn send(self, msg: T) -> Result<(), SendError<T>> {
+        if self.shared.receiver_gone.load(Ordering::SeqCst) {
+            return Err(SendError(msg));
+        }
+        *self.shared.slot.lock().unwrap() = Slot::Full(msg);
+        self.shared.receiving.notify_all();
+        Ok(())
+    }
+
+    //true once the receiver was dropped, nobody is waiting for the value anymore
+    pub fn is_canceled(&self) -> bool {
+        self.shared.receiver_gone.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut slot = self.shared.slot.lock().unwrap();
+        match *slot {
+            Slot::Empty => Err(TryRecvError::Empty),
+            Slot::Full(_) => match mem::replace(&mut *slot, Slot::Taken) {
+                Slot::Full(msg) => Ok(msg),
+                _ => unreachable!(),
+            },
+            Slot::Taken | Slot::Canceled => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    fn recv_deadline(&mut self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let shared = self.shared.clone();
+        wait_until(&shared.receiving, deadline, || match self.try_recv() {
+            Ok(msg) => Some(Ok(msg)),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvTimeoutError::Disconnected)),
+            Err(TryRecvError::Empty) => None,
+        })
+        .unwrap_or(Err(RecvTimeoutError::Timeout))
+    }
+
+    pub fn recv(mut self) -> Result<T, RecvError> {
+        self.recv_deadline(None)
+            .map_err(|_| RecvError::Disconnected)
+    }
+
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now().checked_add(timeout))
+    }
+
+    //true if the sender was dropped without sending, no value will ever arrive
+    pub fn is_canceled(&self) -> bool {
+        matches!(*self.shared.slot.lock().unwrap(), Slot::Canceled)
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let shared = this.shared.clone();
+        let mut entry = this.entry.take();
+        let res = poll_until(&shared.receiving, &mut entry, cx, || match this.try_recv() {
+            Ok(msg) => Some(Ok(msg)),
+            Err(TryRecvError::Disconnected) => Some(Err(RecvError::Disconnected)),
+            Err(TryRecvError::Empty) => None,
+        });
+        this.entry = entry;
+        res
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut slot = self.shared.slot.lock().unwrap();
+        if let Slot::Empty = *slot {
+            *slot = Slot::Canceled;
+            drop(slot);
+            self.shared.receiving.notify_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_gone.store(true, Ordering::SeqCst);
+        if let Some(entry) = self.entry.take() {
+            self.shared.receiving.unregister_task(entry);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
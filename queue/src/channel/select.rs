@@ -0,0 +1,216 @@
+use super::waiter::{Signal, Waiters};
+use super::{Receiver, RecvError, SendError, Sender};
+use crate::util::random;
+use core::fmt;
+use std::error;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Op<'a> {
+    ready: Box<dyn Fn() -> bool + 'a>,
+    waiters: &'a Waiters,
+    //identifies the channel so the selected operation can be checked
+    chan: *const (),
+}
+
+//waits on several channel operations at once and reports the first one
+//that can proceed. ops are scanned from a random start so a busy channel
+//cannot starve the others
+pub struct Select<'a> {
+    ops: Vec<Op<'a>>,
+}
+
+impl<'a> Select<'a> {
+    pub fn new() -> Self {
+        Select { ops: Vec::new() }
+    }
+
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        let shared = &*r.shared;
+        self.ops.push(Op {
+            ready: Box::new(move || shared.recv_ready()),
+            waiters: &shared.receiving,
+            chan: shared as *const _ as *const (),
+        });
+        self.ops.len() - 1
+    }
+
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
+        let shared = &*s.shared;
+        self.ops.push(Op {
+            ready: Box::new(move || shared.send_ready()),
+            waiters: &shared.sending,
+            chan: shared as *const _ as *const (),
+        });
+        self.ops.len() - 1
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        //keep indices of the other operations stable
+        self.ops[index].ready = Box::new(|| false);
+    }
+
+    fn poll(&self) -> Option<usize> {
+        let n = self.ops.len();
+        if n == 0 {
+            return None;
+        }
+        let start = random(n);
+        (0..n)
+            .map(|i| (start + i) % n)
+            .find(|&i| (self.ops[i].ready)())
+    }
+
+    fn selected(&self, index: usize) -> SelectedOperation<'a> {
+        SelectedOperation {
+            index,
+            chan: self.ops[index].chan,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    fn wait(&self, deadline: Option<Instant>) -> Option<usize> {
+        loop {
+            if let Some(i) = self.poll() {
+                return Some(i);
+            }
+
+            let ids: Vec<usize> = self
+                .ops
+                .iter()
+                .map(|op| op.waiters.register(Signal::Thread(thread::current())))
+                .collect();
+
+            let mut ready = self.poll();
+            if ready.is_none() {
+                match deadline {
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now < deadline {
+                            thread::park_timeout(deadline - now);
+                        }
+                    }
+                    None => thread::park(),
+                }
+            }
+
+            let notified: Vec<usize> = self
+                .ops
+                .iter()
+                .zip(ids)
+                .enumerate()
+                .filter(|(_, (op, id))| !op.waiters.unregister(*id))
+                .map(|(i, _)| i)
+                .collect();
+
+            if ready.is_none() {
+                ready = self.poll();
+            }
+            let timed_out = deadline.is_some_and(|d| Instant::now() >= d);
+
+            if ready.is_some() || timed_out {
+                //wakeups for operations we are not going to perform belong to someone else
+                for i in notified {
+                    if Some(i) != ready {
+                        self.ops[i].waiters.notify_one();
+                    }
+                }
+                return ready;
+            }
+        }
+    }
+
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        match self.poll() {
+            Some(i) => Ok(self.selected(i)),
+            None => Err(TrySelectError),
+        }
+    }
+
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        let i = self.wait(None).unwrap();
+        self.selected(i)
+    }
+
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match self.wait(Instant::now().checked_add(timeout)) {
+            Some(i) => Ok(self.selected(i)),
+            None => Err(SelectTimeoutError),
+        }
+    }
+}
+
+impl Default for Select<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
+
+//an operation that was ready when selected. completing it only blocks if
+//another thread raced us to the same channel in the meantime
+#[must_use]
+pub struct SelectedOperation<'a> {
+    index: usize,
+    chan: *const (),
+    _marker: core::marker::PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn recv<T>(self, r: &Receiver<T>) -> Result<T, RecvError> {
+        assert!(
+            core::ptr::eq(&*r.shared as *const _ as *const (), self.chan),
+            "passed a receiver that wasn't selected"
+        );
+        r.recv()
+    }
+
+    pub fn send<T>(self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(
+            core::ptr::eq(&*s.shared as *const _ as *const (), self.chan),
+            "passed a sender that wasn't selected"
+        );
+        s.send(msg)
+    }
+}
+
+impl fmt::Debug for SelectedOperation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectedOperation")
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TrySelectError;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelectTimeoutError;
+
+impl fmt::Display for TrySelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("all operations in select would block")
+    }
+}
+
+impl fmt::Display for SelectTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("timed out waiting on select")
+    }
+}
+
+impl error::Error for TrySelectError {}
+impl error::Error for SelectTimeoutError {}
@@ -0,0 +1,211 @@
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
+use std::thread::{self, Thread};
+use std::time::Instant;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) enum Signal {
+    Thread(Thread),
+    Task(Arc<Task>),
+}
+
+impl Signal {
+    fn wake(self) {
+        match self {
+            Signal::Thread(thread) => thread.unpark(),
+            Signal::Task(task) => {
+                task.woken.store(true, Ordering::Release);
+                task.waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+pub(crate) struct Task {
+    waker: Waker,
+    //set once the entry was taken off the list by a notification
+    woken: AtomicBool,
+}
+
+//a pending future's place in a `Waiters` list, kept from one poll to the next
+pub(crate) struct TaskEntry {
+    id: usize,
+    task: Arc<Task>,
+}
+
+impl TaskEntry {
+    //still queued, and a wakeup would reach the task polling with `waker`
+    fn armed_for(&self, waker: &Waker) -> bool {
+        !self.task.woken.load(Ordering::Acquire) && self.task.waker.will_wake(waker)
+    }
+}
+
+struct Entry {
+    id: usize,
+    signal: Signal,
+}
+
+//threads and tasks blocked on one side of a channel
+pub(crate) struct Waiters {
+    entries: Mutex<Vec<Entry>>,
+    //mirrors `entries.is_empty()` so notifiers can skip the lock
+    empty: AtomicBool,
+}
+
+impl Waiters {
+    pub(crate) fn new() -> Self {
+        Waiters {
+            entries: Mutex::new(Vec::new()),
+            empty: AtomicBool::new(true),
+        }
+    }
+
+    pub(crate) fn register(&self, signal: Signal) -> usize {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(Entry { id, signal });
+        self.empty.store(false, Ordering::Relaxed);
+        drop(entries);
+
+        //pairs with the fence in `notify_*`: either the notifier sees us,
+        //or our re-check after registering sees its update
+        atomic::fence(Ordering::SeqCst);
+        id
+    }
+
+    pub(crate) fn register_task(&self, waker: &Waker) -> TaskEntry {
+        let task = Arc::new(Task {
+            waker: waker.clone(),
+            woken: AtomicBool::new(false),
+        });
+        let id = self.register(Signal::Task(task.clone()));
+        TaskEntry { id, task }
+    }
+
+    //returns false if the entry was already removed by a notification
+    pub(crate) fn unregister(&self, id: usize) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.iter().position(|e| e.id == id) {
+            Some(i) => {
+                entries.remove(i);
+                self.empty.store(entries.is_empty(), Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn unregister_task(&self, entry: TaskEntry) -> bool {
+        self.unregister(entry.id)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        atomic::fence(Ordering::SeqCst);
+        self.empty.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn notify_one(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.empty.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.is_empty() {
+            let entry = entries.remove(0);
+            self.empty.store(entries.is_empty(), Ordering::Relaxed);
+            drop(entries);
+            entry.signal.wake();
+        }
+    }
+
+    pub(crate) fn notify_all(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.empty.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let entries = core::mem::take(&mut *self.entries.lock().unwrap());
+        self.empty.store(true, Ordering::Relaxed);
+        for entry in entries {
+            entry.signal.wake();
+        }
+    }
+}
+
+//runs `attempt` until it yields a value, parking on `waiters` in between.
+//returns None if `deadline` passes first
+pub(crate) fn wait_until<R>(
+    waiters: &Waiters,
+    deadline: Option<Instant>,
+    mut attempt: impl FnMut() -> Option<R>,
+) -> Option<R> {
+    loop {
+        if let Some(r) = attempt() {
+            return Some(r);
+        }
+
+        let id = waiters.register(Signal::Thread(thread::current()));
+        if let Some(r) = attempt() {
+            waiters.unregister(id);
+            return Some(r);
+        }
+
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    let notified = !waiters.unregister(id);
+                    let r = attempt();
+                    if r.is_none() && notified {
+                        //we swallowed a wakeup meant for someone who can use it
+                        waiters.notify_one();
+                    }
+                    return r;
+                }
+                thread::park_timeout(deadline - now);
+            }
+            None => thread::park(),
+        }
+        waiters.unregister(id);
+    }
+}
+
+//async counterpart of `wait_until`. `entry` carries the registration from
+//one poll to the next, a re-poll that is still registered with the same
+//waker only runs `attempt` and doesn't touch the list
+pub(crate) fn poll_until<R>(
+    waiters: &Waiters,
+    entry: &mut Option<TaskEntry>,
+    cx: &mut Context<'_>,
+    mut attempt: impl FnMut() -> Option<R>,
+) -> Poll<R> {
+    if let Some(e) = entry
+        && e.armed_for(cx.waker())
+    {
+        return match attempt() {
+            Some(r) => {
+                waiters.unregister_task(entry.take().unwrap());
+                Poll::Ready(r)
+            }
+            None => Poll::Pending,
+        };
+    }
+
+    if let Some(e) = entry.take() {
+        waiters.unregister_task(e);
+    }
+    if let Some(r) = attempt() {
+        return Poll::Ready(r);
+    }
+
+    let registered = waiters.register_task(cx.waker());
+    if let Some(r) = attempt() {
+        waiters.unregister_task(registered);
+        return Poll::Ready(r);
+    }
+    *entry = Some(registered);
+    Poll::Pending
+}
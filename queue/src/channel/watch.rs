@@ -0,0 +1,213 @@
+use super::waiter::{TaskEntry, Waiters, poll_until, wait_until};
+use super::{RecvError, RecvTimeoutError, SendError};
+use core::fmt;
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
+
+struct Shared<T> {
+    value: RwLock<T>,
+    //bumped under the write lock on every send
+    version: AtomicU64,
+    closed: AtomicBool,
+    receivers: AtomicUsize,
+    changed: Waiters,
+}
+
+//holds only the latest value. receivers remember the version they last
+//looked at and `changed` waits for a newer one, intermediate values are skipped
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    seen: u64,
+}
+
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        value: RwLock::new(init),
+        version: AtomicU64::new(0),
+        closed: AtomicBool::new(false),
+        receivers: AtomicUsize::new(1),
+        changed: Waiters::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, seen: 0 },
+    )
+}
+
+impl<T> Sender<T> {
+    //fails if there is no receiver to see the new value
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.shared.receivers.load(Ordering::SeqCst) == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    //stores the value even without receivers and returns the previous one
+    pub fn send_replace(&self, value: T) -> T {
+        let mut old = Some(value);
+        self.send_modify(|v| old = Some(mem::replace(v, old.take().unwrap())));
+        old.unwrap()
+    }
+
+    pub fn send_modify(&self, modify: impl FnOnce(&mut T)) {
+        let mut value = self.shared.value.write().unwrap();
+        modify(&mut value);
+        self.shared.version.fetch_add(1, Ordering::Release);
+        drop(value);
+        self.shared.changed.notify_all();
+    }
+
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.value.read().unwrap()
+    }
+
+    //a receiver that treats the current value as already seen
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        Receiver {
+            shared: self.shared.clone(),
+            seen: self.shared.version.load(Ordering::Acquire),
+        }
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receivers.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> Receiver<T> {
+    //holding the guard blocks the sender, keep it short
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.value.read().unwrap()
+    }
+
+    //like `borrow` but also marks the value as seen
+    pub fn borrow_and_update(&mut self) -> RwLockReadGuard<'_, T> {
+        let value = self.shared.value.read().unwrap();
+        self.seen = self.shared.version.load(Ordering::Acquire);
+        value
+    }
+
+    //a pending change is reported even after the sender is gone
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let closed = self.shared.closed.load(Ordering::Acquire);
+        if self.shared.version.load(Ordering::Acquire) != self.seen {
+            Ok(true)
+        } else if closed {
+            Err(RecvError::Disconnected)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn check(&mut self) -> Option<Result<(), RecvError>> {
+        //closed first: once it reads true the final version is visible too
+        let closed = self.shared.closed.load(Ordering::Acquire);
+        let version = self.shared.version.load(Ordering::Acquire);
+        if version != self.seen {
+            self.seen = version;
+            Some(Ok(()))
+        } else if closed {
+            Some(Err(RecvError::Disconnected))
+        } else {
+            None
+        }
+    }
+
+    fn changed_deadline(&mut self, deadline: Option<Instant>) -> Result<(), RecvTimeoutError> {
+        let shared = self.shared.clone();
+        wait_until(&shared.changed, deadline, || {
+            self.check()
+                .map(|r| r.map_err(|_| RecvTimeoutError::Disconnected))
+        })
+        .unwrap_or(Err(RecvTimeoutError::Timeout))
+    }
+
+    //blocks until a value newer than the last seen one was sent, and marks it seen
+    pub fn changed(&mut self) -> Result<(), RecvError> {
+        self.changed_deadline(None)
+            .map_err(|_| RecvError::Disconnected)
+    }
+
+    pub fn changed_timeout(&mut self, timeout: Duration) -> Result<(), RecvTimeoutError> {
+        self.changed_deadline(Instant::now().checked_add(timeout))
+    }
+
+    pub fn changed_async(&mut self) -> ChangedFuture<'_, T> {
+        ChangedFuture {
+            receiver: self,
+            entry: None,
+        }
+    }
+}
+
+pub struct ChangedFuture<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    entry: Option<TaskEntry>,
+}
+
+impl<T> Future for ChangedFuture<'_, T> {
+    type Output = Result<(), RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let shared = this.receiver.shared.clone();
+        poll_until(&shared.changed, &mut this.entry, cx, || this.receiver.check())
+    }
+}
+
+impl<T> Drop for ChangedFuture<'_, T> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.receiver.shared.changed.unregister_task(entry);
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.receivers.fetch_add(1, Ordering::SeqCst);
+        Receiver {
+            shared: self.shared.clone(),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.changed.notify_all();
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receivers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Mutex;
+
+//hand-off cell for rendezvous channels, holds at most one message in flight.
+//every offered message gets a ticket so its sender can tell when it was taken
+pub(crate) struct Slot<T> {
+    inner: Mutex<Inner<T>>,
+}
+
+struct Inner<T> {
+    msg: Option<T>,
+    sent: u64,
+    taken: u64,
+}
+
+impl<T> Slot<T> {
+    pub(crate) fn new() -> Self {
+        Slot {
+            inner: Mutex::new(Inner {
+                msg: None,
+                sent: 0,
+                taken: 0,
+            }),
+        }
+    }
+
+    pub(crate) fn offer(&self, msg: T) -> Result<u64, T> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.msg.is_some() {
+            return Err(msg);
+        }
+        inner.msg = Some(msg);
+        inner.sent += 1;
+        Ok(inner.sent)
+    }
+
+    pub(crate) fn take(&self) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap();
+        let msg = inner.msg.take();
+        if msg.is_some() {
+            inner.taken += 1;
+        }
+        msg
+    }
+
+    pub(crate) fn is_taken(&self, ticket: u64) -> bool {
+        self.inner.lock().unwrap().taken >= ticket
+    }
+
+    //takes back the message with `ticket` if no receiver got to it yet
+    pub(crate) fn reclaim(&self, ticket: u64) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.taken >= ticket {
+            return None;
+        }
+        inner.taken += 1;
+        inner.msg.take()
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.inner.lock().unwrap().msg.is_some()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::reclaim::Reclaim;
+use crate::{ArrayQueue, SegQueue, first, second};
+use crossbeam::queue as cb;
+
+//common interface over the queues in this crate, so tests and benchmarks
+//can be written once and run against every implementation.
+//unbounded queues always accept a push, bounded ones hand the value back when full
+pub trait ConcurrentQueue<T> {
+    fn push(&self, value: T) -> Result<(), T>;
+
+    fn pop(&self) -> Option<T>;
+
+    fn is_empty(&self) -> bool;
+
+    //None if the queue can't count its items cheaply
+    fn len(&self) -> Option<usize> {
+        None
+    }
+
+    //None for unbounded queues
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<T> ConcurrentQueue<T> for first::AtomicQueue<T> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.enqueue(value);
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.dequeue()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T, R: Reclaim> ConcurrentQueue<T> for second::AtomicQueue<T, R> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.enqueue(value);
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.dequeue()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> ConcurrentQueue<T> for SegQueue<T> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T> ConcurrentQueue<T> for ArrayQueue<T> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.push(value)
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
+}
+
+//crossbeam's originals, kept as the baseline the benchmarks compare against
+impl<T> ConcurrentQueue<T> for cb::SegQueue<T> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T> ConcurrentQueue<T> for cb::ArrayQueue<T> {
+    fn push(&self, value: T) -> Result<(), T> {
+        self.push(value)
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
+}
@@ -0,0 +1,445 @@
+use crate::reclaim::{Epoch, Reclaim};
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use crossbeam::utils::CachePadded;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicIsize, AtomicPtr, Ordering};
+
+const MIN_CAP: usize = 64;
+//upper bound on tasks moved by one `steal_batch_into`
+const MAX_BATCH: usize = 32;
+
+//circular buffer, `cap` is a power of two and indices wrap around it
+struct Buffer<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+impl<T> Buffer<T> {
+    fn alloc(cap: usize) -> *mut Self {
+        debug_assert!(cap.is_power_of_two());
+        Box::into_raw(Box::new(Buffer {
+            slots: (0..cap)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+        }))
+    }
+
+    fn cap(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn at(&self, i: isize) -> *mut MaybeUninit<T> {
+        self.slots[i as usize & (self.cap() - 1)].get()
+    }
+
+    unsafe fn write(&self, i: isize, task: T) {
+        unsafe { self.at(i).write(MaybeUninit::new(task)) }
+    }
+
+    //a stealer may read a slot the owner is overwriting, the copy is only
+    //assumed init once the read is confirmed by a cas on `front`
+    unsafe fn read(&self, i: isize) -> MaybeUninit<T> {
+        unsafe { ptr::read_volatile(self.at(i)) }
+    }
+}
+
+struct Inner<T> {
+    //next index to steal from
+    front: AtomicIsize,
+    //next index the owner pushes to
+    back: AtomicIsize,
+    buffer: CachePadded<AtomicPtr<Buffer<T>>>,
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let b = *self.back.get_mut();
+        let f = *self.front.get_mut();
+        let buffer = unsafe { Box::from_raw(*self.buffer.get_mut()) };
+        let mut i = f;
+        while i != b {
+            unsafe { (*buffer.at(i)).assume_init_drop() };
+            i = i.wrapping_add(1);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    Fifo,
+    Lifo,
+}
+
+//owner side of a chase-lev deque. only this handle pushes, and it pops from
+//the back (lifo) or the front (fifo) while stealers always take from the front
+pub struct Worker<T> {
+    inner: Arc<CachePadded<Inner<T>>>,
+    //same as `inner.buffer`, only the owner ever swaps it
+    buffer: Cell<*mut Buffer<T>>,
+    flavor: Flavor,
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<T: Send> Send for Worker<T> {}
+
+pub struct Stealer<T> {
+    inner: Arc<CachePadded<Inner<T>>>,
+    flavor: Flavor,
+}
+
+unsafe impl<T: Send> Send for Stealer<T> {}
+unsafe impl<T: Send> Sync for Stealer<T> {}
+
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Steal<T> {
+    Empty,
+    Success(T),
+    //lost a race with another thread, the deque may still have tasks
+    Retry,
+}
+
+impl<T> Steal<T> {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Steal::Empty)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Steal::Success(_))
+    }
+
+    pub fn is_retry(&self) -> bool {
+        matches!(self, Steal::Retry)
+    }
+
+    pub fn success(self) -> Option<T> {
+        match self {
+            Steal::Success(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Worker<T> {
+    fn with_flavor(flavor: Flavor) -> Self {
+        let buffer = Buffer::alloc(MIN_CAP);
+        Worker {
+            inner: Arc::new(CachePadded::new(Inner {
+                front: AtomicIsize::new(0),
+                back: AtomicIsize::new(0),
+                buffer: CachePadded::new(AtomicPtr::new(buffer)),
+            })),
+            buffer: Cell::new(buffer),
+            flavor,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn new_fifo() -> Self {
+        Self::with_flavor(Flavor::Fifo)
+    }
+
+    pub fn new_lifo() -> Self {
+        Self::with_flavor(Flavor::Lifo)
+    }
+
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: self.inner.clone(),
+            flavor: self.flavor,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::SeqCst);
+        b.wrapping_sub(f).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //moves the live tasks into a buffer of `new_cap` and retires the old one.
+    //stealers that loaded the old buffer keep reading it until their guard drops
+    fn resize(&self, new_cap: usize) {
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::Relaxed);
+        let old = self.buffer.get();
+        let new = Buffer::alloc(new_cap);
+
+        let mut i = f;
+        while i != b {
+            unsafe { ptr::copy_nonoverlapping((*old).at(i), (*new).at(i), 1) };
+            i = i.wrapping_add(1);
+        }
+
+        let guard = Epoch::guard();
+        self.buffer.set(new);
+        self.inner.buffer.store(new, Ordering::Release);
+        unsafe { Epoch::retire(&guard, old) };
+    }
+
+    //makes room for `n` more tasks without another resize
+    fn reserve(&self, n: usize) {
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::SeqCst);
+        let len = b.wrapping_sub(f).max(0) as usize;
+        let cap = unsafe { (*self.buffer.get()).cap() };
+        if cap - len < n {
+            self.resize((len + n).next_power_of_two().max(cap * 2));
+        }
+    }
+
+    pub fn push(&self, task: T) {
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::Acquire);
+        let len = b.wrapping_sub(f);
+
+        let cap = unsafe { (*self.buffer.get()).cap() };
+        if len >= cap as isize {
+            self.resize(2 * cap);
+        }
+        unsafe { (*self.buffer.get()).write(b, task) };
+
+        //the task must be visible before stealers can see the new `back`
+        atomic::fence(Ordering::Release);
+        self.inner.back.store(b.wrapping_add(1), Ordering::Relaxed);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        match self.flavor {
+            Flavor::Fifo => self.pop_front(),
+            Flavor::Lifo => self.pop_back(),
+        }
+    }
+
+    fn pop_front(&self) -> Option<T> {
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::Relaxed);
+        let len = b.wrapping_sub(f);
+        if len <= 0 {
+            return None;
+        }
+
+        //competes with stealers for the same end
+        let f = self.inner.front.fetch_add(1, Ordering::SeqCst);
+        if b.wrapping_sub(f.wrapping_add(1)) < 0 {
+            self.inner.front.store(f, Ordering::Relaxed);
+            return None;
+        }
+
+        let buffer = self.buffer.get();
+        let task = unsafe { (*buffer).read(f).assume_init() };
+        let cap = unsafe { (*buffer).cap() };
+        if cap > MIN_CAP && len <= cap as isize / 4 {
+            self.resize(cap / 2);
+        }
+        Some(task)
+    }
+
+    fn pop_back(&self) -> Option<T> {
+        let b = self.inner.back.load(Ordering::Relaxed).wrapping_sub(1);
+        self.inner.back.store(b, Ordering::Relaxed);
+        //claim the slot before looking at `front`, pairs with the fence in `steal`
+        atomic::fence(Ordering::SeqCst);
+        let f = self.inner.front.load(Ordering::Relaxed);
+
+        let len = b.wrapping_sub(f);
+        if len < 0 {
+            self.inner.back.store(b.wrapping_add(1), Ordering::Relaxed);
+            return None;
+        }
+
+        let buffer = self.buffer.get();
+        let task = unsafe { (*buffer).read(b) };
+        if len == 0 {
+            //last task, race the stealers for it through `front`
+            let won = self
+                .inner
+                .front
+                .compare_exchange(f, f.wrapping_add(1), Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok();
+            self.inner.back.store(b.wrapping_add(1), Ordering::Relaxed);
+            return won.then(|| unsafe { task.assume_init() });
+        }
+
+        let cap = unsafe { (*buffer).cap() };
+        if cap > MIN_CAP && len < cap as isize / 4 {
+            self.resize(cap / 2);
+        }
+        Some(unsafe { task.assume_init() })
+    }
+}
+
+impl<T> fmt::Debug for Worker<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Worker { .. }")
+    }
+}
+
+impl<T> Stealer<T> {
+    pub fn is_empty(&self) -> bool {
+        let f = self.inner.front.load(Ordering::Acquire);
+        atomic::fence(Ordering::SeqCst);
+        let b = self.inner.back.load(Ordering::Acquire);
+        b.wrapping_sub(f) <= 0
+    }
+
+    pub fn len(&self) -> usize {
+        let f = self.inner.front.load(Ordering::Acquire);
+        atomic::fence(Ordering::SeqCst);
+        let b = self.inner.back.load(Ordering::Acquire);
+        b.wrapping_sub(f).max(0) as usize
+    }
+
+    //takes one task from the front
+    pub fn steal(&self) -> Steal<T> {
+        let f = self.inner.front.load(Ordering::Acquire);
+        atomic::fence(Ordering::SeqCst);
+
+        let mut guard = Epoch::guard();
+        let b = self.inner.back.load(Ordering::Acquire);
+        if b.wrapping_sub(f) <= 0 {
+            return Steal::Empty;
+        }
+
+        let buffer = Epoch::protect(&mut guard, 0, &self.inner.buffer);
+        let task = unsafe { (*buffer).read(f) };
+
+        //a swapped buffer means the read may have come from the retired copy
+        //of a slot the owner already reused
+        if self.inner.buffer.load(Ordering::Acquire) != buffer
+            || self
+                .inner
+                .front
+                .compare_exchange(f, f.wrapping_add(1), Ordering::SeqCst, Ordering::Relaxed)
+                .is_err()
+        {
+            return Steal::Retry;
+        }
+        Steal::Success(unsafe { task.assume_init() })
+    }
+
+    //moves up to half of the tasks (at most MAX_BATCH) into `dest`.
+    //`dest` keeps them in the order they were stolen
+    pub fn steal_batch_into(&self, dest: &Worker<T>) -> Steal<()> {
+        if Arc::ptr_eq(&self.inner, &dest.inner) {
+            return Steal::Empty;
+        }
+
+        let mut f = self.inner.front.load(Ordering::Acquire);
+        atomic::fence(Ordering::SeqCst);
+
+        let mut guard = Epoch::guard();
+        let b = self.inner.back.load(Ordering::Acquire);
+        let len = b.wrapping_sub(f);
+        if len <= 0 {
+            return Steal::Empty;
+        }
+        let n = ((len as usize).div_ceil(2)).min(MAX_BATCH);
+
+        dest.reserve(n);
+        let dest_b = dest.inner.back.load(Ordering::Relaxed);
+        let dest_buffer = dest.buffer.get();
+        let buffer = Epoch::protect(&mut guard, 0, &self.inner.buffer);
+
+        let stolen = match self.flavor {
+            //the owner only takes from the front too, and always through `front`,
+            //so one cas commits the whole batch. copies are staged past dest's
+            //`back`, invisible until then
+            Flavor::Fifo => {
+                for i in 0..n as isize {
+                    unsafe {
+                        let task = (*buffer).read(f.wrapping_add(i));
+                        (*dest_buffer).at(dest_b.wrapping_add(i)).write(task);
+                    }
+                }
+
+                if self.inner.buffer.load(Ordering::Acquire) != buffer
+                    || self
+                        .inner
+                        .front
+                        .compare_exchange(
+                            f,
+                            f.wrapping_add(n as isize),
+                            Ordering::SeqCst,
+                            Ordering::Relaxed,
+                        )
+                        .is_err()
+                {
+                    return Steal::Retry;
+                }
+                n
+            }
+            //the owner pops from the back without touching `front` until the
+            //last task, so a batch cas could claim tasks it already took.
+            //claim one task per cas and re-check `back` before the next
+            Flavor::Lifo => {
+                let mut stolen = 0;
+                loop {
+                    let task = unsafe { (*buffer).read(f) };
+
+                    if self.inner.buffer.load(Ordering::Acquire) != buffer
+                        || self
+                            .inner
+                            .front
+                            .compare_exchange(
+                                f,
+                                f.wrapping_add(1),
+                                Ordering::SeqCst,
+                                Ordering::Relaxed,
+                            )
+                            .is_err()
+                    {
+                        break;
+                    }
+                    unsafe {
+                        (*dest_buffer)
+                            .at(dest_b.wrapping_add(stolen as isize))
+                            .write(task)
+                    };
+                    stolen += 1;
+                    f = f.wrapping_add(1);
+                    if stolen == n {
+                        break;
+                    }
+
+                    atomic::fence(Ordering::SeqCst);
+                    let b = self.inner.back.load(Ordering::Acquire);
+                    if b.wrapping_sub(f) <= 0 {
+                        break;
+                    }
+                }
+                if stolen == 0 {
+                    return Steal::Retry;
+                }
+                stolen
+            }
+        };
+
+        atomic::fence(Ordering::Release);
+        dest.inner
+            .back
+            .store(dest_b.wrapping_add(stolen as isize), Ordering::Relaxed);
+        Steal::Success(())
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Stealer {
+            inner: self.inner.clone(),
+            flavor: self.flavor,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stealer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Stealer { .. }")
+    }
+}
@@ -1,7 +1,8 @@
 use core::ptr;
 use std::sync::atomic::{AtomicPtr, Ordering};
 use core::cell::UnsafeCell;
-use core::mem::{MaybeUninit};
+use core::mem::MaybeUninit;
+use crate::hazard::{self, HazardPointer};
 
 #[repr(align(64))]
 pub struct AtomicQueue<T>{
@@ -13,27 +14,37 @@ type Link<T> = AtomicPtr<UnsafeCell<Node<T>>>;
 
 
 pub struct Node<T>{
-    data: T,
+    //uninit for the dummy and for a node whose data was already taken
+    data: MaybeUninit<T>,
     next: Link<T>,
 }
 
 impl<T> Node<T>{
-fn dummy() -> *mut UnsafeCell<Node<T>>{
-    Box::into_raw(Box::new(
-        UnsafeCell::new(Node{
-        data: unsafe { core::mem::zeroed() },
-        next: null_atomic_ptr(),
-        }
+    fn alloc(data: MaybeUninit<T>) -> *mut UnsafeCell<Node<T>>{
+        Box::into_raw(Box::new(
+            UnsafeCell::new(Node{
+                data,
+                next: null_atomic_ptr(),
+            }
         )))
     }
 
+    fn dummy() -> *mut UnsafeCell<Node<T>>{
+        Self::alloc(MaybeUninit::uninit())
+    }
+
+    //caller keeps `ptr` protected for as long as it uses the link
+    #[inline(always)]
+    unsafe fn next<'a>(ptr: *mut UnsafeCell<Node<T>>) -> &'a Link<T>{
+        unsafe{ &(*(*ptr).get()).next }
+    }
 }
 
 impl<T> AtomicQueue<T>{
 
     pub fn new() -> Self{
-            let node = Node::dummy();
-            AtomicQueue{
+        let node = Node::dummy();
+        AtomicQueue{
             head: AtomicPtr::new(node),
             tail: AtomicPtr::new(node),
         }
@@ -41,36 +52,30 @@ impl<T> AtomicQueue<T>{
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool{
-        unsafe{
-            (*(*self.head.load(Ordering::Acquire)).get()).next.load(Ordering::Acquire).is_null()
-            
-        }
+        let mut hp = HazardPointer::new();
+        let head = hp.protect(&self.head);
+        unsafe{ Node::next(head) }.load(Ordering::Acquire).is_null()
     }
 
     pub fn enqueue(&self, data: T){
         //add to tail
-        let new_node = Box::into_raw(Box::new(
-                UnsafeCell::new(Node{
-                    data,
-                    next: null_atomic_ptr(),
-                }
-            )));
+        let new_node = Node::alloc(MaybeUninit::new(data));
+        let mut hp = HazardPointer::new();
 
         loop{
-            let tail = self.tail.load(Ordering::Acquire);
-            let next = unsafe{(*(*tail).get()).next.load(Ordering::Acquire)};
+            //tail can't be retired while protected, head never passes it
+            let tail = hp.protect(&self.tail);
+            let next = unsafe{ Node::next(tail) }.load(Ordering::Acquire);
 
             if next.is_null(){
 
-                let success = unsafe{
-                    (*(*tail).get()).next.compare_exchange(
-                        ptr::null_mut(),
-                        new_node,
-                        Ordering::AcqRel,
-                        Ordering::Relaxed,
-                    ).is_ok()
-                };
-                
+                let success = unsafe{ Node::next(tail) }.compare_exchange(
+                    ptr::null_mut(),
+                    new_node,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                ).is_ok();
+
                 if success{
                     let _ = self.tail.compare_exchange(
                         tail,
@@ -101,42 +106,71 @@ impl<T> AtomicQueue<T>{
     */
 
     pub fn dequeue(&self) -> Option<T>{
-        //remove from head
-        unsafe{
-            loop{
-                let head = self.head.load(Ordering::Acquire);
-                let next = (*(*head).get()).next.load(Ordering::Acquire);
-                if next.is_null(){
-                    return None;        
-                }
+        //remove from head. hp_head keeps the old dummy alive while we read its
+        //next link, hp_next keeps the new dummy alive while we move its data out
+        let mut hp_head = HazardPointer::new();
+        let mut hp_next = HazardPointer::new();
+        loop{
+            let head = hp_head.protect(&self.head);
+            let next = hp_next.protect(unsafe{ Node::next(head) });
 
-                if self.head.compare_exchange(
-                        head,
-                        next,
-                        Ordering::Release,
-                        Ordering::Relaxed,
-                ).is_ok(){
-                    let _ = Box::from_raw(head);
-                    return Some(Self::take_data(next)); 
-                }
+            //head moved on, `next` may have been retired before it was protected
+            if self.head.load(Ordering::Acquire) != head{
+                continue;
+            }
+            if next.is_null(){
+                return None;
+            }
+
+            //tail is lagging behind, push it forward before head can overtake it
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail{
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+
+            if self.head.compare_exchange(
+                    head,
+                    next,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+            ).is_ok(){
+                //`next` is the new dummy, only its data leaves the queue
+                let data = unsafe{ (*(*next).get()).data.assume_init_read() };
+                unsafe{ hazard::retire(head) };
+                return Some(data);
             }
         }
-    } 
-
-    
-        #[inline(always)]
-            fn take_data(ptr: *mut UnsafeCell<Node<T>>) -> T {
-        unsafe {
-            let b = Box::from_raw((*ptr).get());            
-            let mut m: MaybeUninit<T> = MaybeUninit::uninit();
-            let data = core::mem::replace(&mut *m.as_mut_ptr() , b.data);
-            
-            data
-        // Drop boxed_cell here, Node is dropped (except data, which we moved)
-        //THIS IS UB    
-        }
-        }
+    }
+
+}
+
+impl<T> Default for AtomicQueue<T>{
+    fn default() -> Self{
+        Self::new()
+    }
+}
 
+impl<T> Drop for AtomicQueue<T>{
+    fn drop(&mut self){
+        //no other thread can reach the nodes anymore, free them directly
+        let mut cur = *self.head.get_mut();
+        let mut dummy = true;
+        while !cur.is_null(){
+            let mut node = unsafe{ Box::from_raw(cur) };
+            let node = node.get_mut();
+            if !dummy{
+                unsafe{ node.data.assume_init_drop() };
+            }
+            dummy = false;
+            cur = *node.next.get_mut();
+        }
+    }
 }
 
 unsafe impl<T: Send> Sync for AtomicQueue<T> {}
@@ -148,8 +182,3 @@ unsafe impl<T: Send> Send for AtomicQueue<T> {}
 fn null_atomic_ptr<T>() -> AtomicPtr<T>{
     AtomicPtr::new(ptr::null_mut())
 }
-
-
-
-
-
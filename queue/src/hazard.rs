@@ -0,0 +1,237 @@
+use core::cell::RefCell;
+use core::mem;
+use core::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+//one published hazard. records are never freed, a released record is
+//marked inactive and handed to the next thread that asks for one
+struct Record {
+    ptr: AtomicPtr<()>,
+    active: AtomicBool,
+    next: *mut Record,
+}
+
+static RECORDS: AtomicPtr<Record> = AtomicPtr::new(ptr::null_mut());
+static RECORD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+//retired nodes left behind by threads that exited before they could be freed
+static ORPHANS: Mutex<Vec<Retired>> = Mutex::new(Vec::new());
+
+const MIN_SCAN_THRESHOLD: usize = 64;
+
+struct Retired {
+    ptr: *mut (),
+    deleter: unsafe fn(*mut ()),
+}
+
+unsafe impl Send for Retired {}
+
+struct Local {
+    retired: Vec<Retired>,
+    //records released by this thread, still marked active so nobody else takes them
+    cached: Vec<*mut Record>,
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        for &rec in &self.cached {
+            unsafe { (*rec).active.store(false, Ordering::Release) };
+        }
+        scan(&mut self.retired);
+        if !self.retired.is_empty() {
+            ORPHANS.lock().unwrap().append(&mut self.retired);
+        }
+    }
+}
+
+thread_local! {
+    static LOCAL: RefCell<Local> = const {
+        RefCell::new(Local {
+            retired: Vec::new(),
+            cached: Vec::new(),
+        })
+    };
+}
+
+fn acquire_record() -> *mut Record {
+    if let Ok(Some(rec)) = LOCAL.try_with(|l| l.borrow_mut().cached.pop()) {
+        return rec;
+    }
+
+    let mut cur = RECORDS.load(Ordering::Acquire);
+    while !cur.is_null() {
+        let rec = unsafe { &*cur };
+        if !rec.active.load(Ordering::Relaxed)
+            && rec
+                .active
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        {
+            return cur;
+        }
+        cur = rec.next;
+    }
+
+    let rec = Box::into_raw(Box::new(Record {
+        ptr: AtomicPtr::new(ptr::null_mut()),
+        active: AtomicBool::new(true),
+        next: ptr::null_mut(),
+    }));
+    let mut head = RECORDS.load(Ordering::Acquire);
+    loop {
+        unsafe { (*rec).next = head };
+        match RECORDS.compare_exchange_weak(head, rec, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => break,
+            Err(h) => head = h,
+        }
+    }
+    RECORD_COUNT.fetch_add(1, Ordering::Relaxed);
+    rec
+}
+
+fn release_record(rec: *mut Record) {
+    unsafe { (*rec).ptr.store(ptr::null_mut(), Ordering::Release) };
+    let cached = LOCAL.try_with(|l| l.borrow_mut().cached.push(rec));
+    if cached.is_err() {
+        //thread is shutting down, give the record back to everyone
+        unsafe { (*rec).active.store(false, Ordering::Release) };
+    }
+}
+
+fn protected() -> Vec<*mut ()> {
+    let mut hazards = Vec::new();
+    let mut cur = RECORDS.load(Ordering::Acquire);
+    while !cur.is_null() {
+        let rec = unsafe { &*cur };
+        let p = rec.ptr.load(Ordering::Acquire);
+        if !p.is_null() {
+            hazards.push(p);
+        }
+        cur = rec.next;
+    }
+    hazards.sort_unstable();
+    hazards
+}
+
+//frees every retired node no hazard points at, keeps the rest
+fn scan(retired: &mut Vec<Retired>) {
+    if let Ok(mut orphans) = ORPHANS.try_lock() {
+        retired.append(&mut orphans);
+    }
+
+    //pairs with the fence in `protect`: a reader that published its hazard
+    //before this point is seen, one that publishes later re-validates and misses the node
+    atomic::fence(Ordering::SeqCst);
+    let hazards = protected();
+
+    retired.retain(|r| {
+        if hazards.binary_search(&r.ptr).is_ok() {
+            true
+        } else {
+            unsafe { (r.deleter)(r.ptr) };
+            false
+        }
+    });
+}
+
+fn scan_threshold() -> usize {
+    (2 * RECORD_COUNT.load(Ordering::Relaxed)).max(MIN_SCAN_THRESHOLD)
+}
+
+unsafe fn drop_box<T>(ptr: *mut ()) {
+    drop(unsafe { Box::from_raw(ptr as *mut T) });
+}
+
+/// Hands `ptr` over to be freed once no hazard pointer protects it.
+///
+/// # Safety
+/// `ptr` must come from `Box::into_raw`, be retired only once, and already be
+/// unreachable for readers that have not protected it yet.
+pub unsafe fn retire<T>(ptr: *mut T) {
+    unsafe { retire_with(ptr as *mut (), drop_box::<T>) }
+}
+
+/// Like [`retire`], but frees the node by calling `deleter(ptr)`.
+///
+/// # Safety
+/// Same as [`retire`], and `deleter` must be sound to call on `ptr` from any thread.
+pub unsafe fn retire_with(ptr: *mut (), deleter: unsafe fn(*mut ())) {
+    let r = Retired { ptr, deleter };
+    let pushed = LOCAL.try_with(|l| {
+        let retired = &mut l.borrow_mut().retired;
+        retired.push(r);
+        retired.len() >= scan_threshold()
+    });
+    match pushed {
+        Ok(true) => flush(),
+        Ok(false) => {}
+        Err(_) => ORPHANS.lock().unwrap().push(Retired { ptr, deleter }),
+    }
+}
+
+//scans this thread's retired list now instead of waiting for the threshold
+pub fn flush() {
+    //scan outside the borrow, a deleter may retire nodes of its own
+    let Ok(mut retired) = LOCAL.try_with(|l| mem::take(&mut l.borrow_mut().retired)) else {
+        return;
+    };
+    scan(&mut retired);
+    let _ = LOCAL.try_with(|l| l.borrow_mut().retired.append(&mut retired));
+}
+
+//a single hazard slot owned by the current thread
+pub struct HazardPointer {
+    rec: *mut Record,
+}
+
+impl HazardPointer {
+    pub fn new() -> Self {
+        HazardPointer {
+            rec: acquire_record(),
+        }
+    }
+
+    //loads `src` and keeps the result from being freed until the next
+    //`protect`/`reset` or drop. retries until the pointer is stable
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let rec = unsafe { &*self.rec };
+        let mut p = src.load(Ordering::Relaxed);
+        loop {
+            rec.ptr.store(p as *mut (), Ordering::SeqCst);
+            //pairs with the fence in `scan`, without it the re-check may be
+            //ordered before the store and validate a pointer scan never saw
+            atomic::fence(Ordering::SeqCst);
+            let again = src.load(Ordering::Acquire);
+            if again == p {
+                return p;
+            }
+            p = again;
+        }
+    }
+
+    //publishes `p` without validation, the caller must know it is still reachable
+    pub fn set<T>(&mut self, p: *mut T) {
+        unsafe { &*self.rec }
+            .ptr
+            .store(p as *mut (), Ordering::SeqCst);
+    }
+
+    pub fn reset(&mut self) {
+        unsafe { &*self.rec }
+            .ptr
+            .store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Default for HazardPointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        release_record(self.rec);
+    }
+}
@@ -1,5 +1,29 @@
+pub mod first;
 pub mod second;
-//mod segQueue;
+#[allow(non_snake_case)]
+pub mod segQueue;
+#[allow(non_snake_case)]
+pub mod arrayQueue;
+pub mod channel;
+//only there so the async tests can run futures without an external runtime
+#[doc(hidden)]
+pub mod executor;
+pub mod hazard;
+pub mod reclaim;
+pub mod concurrent;
+pub mod stack;
+pub mod deque;
+pub mod pool;
+pub mod priority;
+pub mod mpsc;
+pub mod spsc;
+mod util;
 
 pub use second::AtomicQueue;
-//pub use segQueue::SegQueue;
+pub use segQueue::SegQueue;
+pub use arrayQueue::ArrayQueue;
+pub use reclaim::{Epoch, Hazard, Reclaim};
+pub use concurrent::ConcurrentQueue;
+pub use stack::Stack;
+pub use pool::ThreadPool;
+pub use priority::PriorityQueue;
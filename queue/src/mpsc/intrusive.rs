@@ -0,0 +1,211 @@
+use super::TryPopError;
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+use crossbeam::utils::{Backoff, CachePadded};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+//hook embedded in a user struct so it can sit in the queue without another allocation
+pub struct Link {
+    next: AtomicPtr<Link>,
+}
+
+impl Link {
+    pub const fn new() -> Self {
+        Link {
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Link { .. }")
+    }
+}
+
+/// Maps between a struct and the [`Link`] embedded in it.
+///
+/// # Safety
+/// `link` must return a pointer to a `Link` field inside `*this`, derived from
+/// `this` (e.g. `ptr::addr_of!((*this).link)`), and `from_link` must undo it
+/// (e.g. `link.byte_sub(mem::offset_of!(Self, link)).cast()`).
+pub unsafe trait Linked {
+    /// # Safety
+    /// `this` must point to a live `Self`.
+    unsafe fn link(this: *const Self) -> *const Link;
+
+    /// # Safety
+    /// `link` must have been returned by [`Linked::link`].
+    unsafe fn from_link(link: *const Link) -> *const Self;
+}
+
+struct Inner<T: Linked> {
+    //producers swap themselves in here
+    head: CachePadded<AtomicPtr<Link>>,
+    //only touched by the consumer
+    tail: CachePadded<UnsafeCell<*mut Link>>,
+    //keeps the list non-empty, it is put back in whenever the last node is popped
+    stub: *mut Link,
+    _marker: PhantomData<Box<T>>,
+}
+
+unsafe impl<T: Linked + Send> Send for Inner<T> {}
+unsafe impl<T: Linked + Send> Sync for Inner<T> {}
+
+impl<T: Linked> Inner<T> {
+    fn push_link(&self, link: *mut Link) {
+        unsafe { (*link).next.store(ptr::null_mut(), Ordering::Relaxed) };
+        let prev = self.head.swap(link, Ordering::AcqRel);
+        //between the swap and this store the list is cut, the consumer sees `Inconsistent`
+        unsafe { (*prev).next.store(link, Ordering::Release) };
+    }
+
+    //only called by the single consumer
+    unsafe fn try_pop(&self) -> Result<Box<T>, TryPopError> {
+        unsafe {
+            let tail_cell = self.tail.get();
+            let mut tail = *tail_cell;
+            let mut next = (*tail).next.load(Ordering::Acquire);
+
+            if tail == self.stub {
+                if next.is_null() {
+                    return Err(if self.head.load(Ordering::Acquire) == self.stub {
+                        TryPopError::Empty
+                    } else {
+                        TryPopError::Inconsistent
+                    });
+                }
+                *tail_cell = next;
+                tail = next;
+                next = (*next).next.load(Ordering::Acquire);
+            }
+
+            if !next.is_null() {
+                *tail_cell = next;
+                return Ok(Self::unlink(tail));
+            }
+
+            //`tail` is the last node, it can only go once the stub sits behind it
+            if tail != self.head.load(Ordering::Acquire) {
+                return Err(TryPopError::Inconsistent);
+            }
+            self.push_link(self.stub);
+            next = (*tail).next.load(Ordering::Acquire);
+            if !next.is_null() {
+                *tail_cell = next;
+                return Ok(Self::unlink(tail));
+            }
+            Err(TryPopError::Inconsistent)
+        }
+    }
+
+    unsafe fn unlink(link: *mut Link) -> Box<T> {
+        unsafe { Box::from_raw(T::from_link(link) as *mut T) }
+    }
+
+    fn is_empty(&self) -> bool {
+        let tail = unsafe { *self.tail.get() };
+        tail == self.stub && self.head.load(Ordering::Acquire) == self.stub
+    }
+}
+
+impl<T: Linked> Drop for Inner<T> {
+    fn drop(&mut self) {
+        //no producer is left, so the list is never inconsistent here
+        while let Ok(node) = unsafe { self.try_pop() } {
+            drop(node);
+        }
+        drop(unsafe { Box::from_raw(self.stub) });
+    }
+}
+
+pub struct Producer<T: Linked> {
+    inner: Arc<Inner<T>>,
+}
+
+//the only handle allowed to pop. `!Sync`, so it can be moved to another
+//thread but never shared, which keeps the queue single-consumer
+pub struct Consumer<T: Linked> {
+    inner: Arc<Inner<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+pub fn queue<T: Linked>() -> (Producer<T>, Consumer<T>) {
+    let stub = Box::into_raw(Box::new(Link::new()));
+    let inner = Arc::new(Inner {
+        head: CachePadded::new(AtomicPtr::new(stub)),
+        tail: CachePadded::new(UnsafeCell::new(stub)),
+        stub,
+        _marker: PhantomData,
+    });
+    (
+        Producer {
+            inner: inner.clone(),
+        },
+        Consumer {
+            inner,
+            _not_sync: PhantomData,
+        },
+    )
+}
+
+impl<T: Linked> Producer<T> {
+    //one swap, never waits for other producers or the consumer
+    pub fn push(&self, node: Box<T>) {
+        let node = Box::into_raw(node);
+        let link = unsafe { T::link(node) } as *mut Link;
+        self.inner.push_link(link);
+    }
+}
+
+impl<T: Linked> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Producer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Linked> Consumer<T> {
+    //`Inconsistent` means a producer is halfway through a push, retrying soon will succeed
+    pub fn try_pop(&self) -> Result<Box<T>, TryPopError> {
+        unsafe { self.inner.try_pop() }
+    }
+
+    //spins over half-finished pushes, returns None only when the queue is empty
+    pub fn pop(&self) -> Option<Box<T>> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_pop() {
+                Ok(node) => return Some(node),
+                Err(TryPopError::Empty) => return None,
+                Err(TryPopError::Inconsistent) => backoff.snooze(),
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T: Linked> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+impl<T: Linked> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
@@ -0,0 +1,99 @@
+//vyukov's multi-producer single-consumer queue. producers only swap the
+//head pointer, the consumer walks the list with plain loads and no epoch pinning
+pub mod intrusive;
+
+use core::fmt;
+use core::mem;
+use core::ptr;
+use intrusive::{Link, Linked};
+use std::error;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryPopError {
+    Empty,
+    //a producer swapped itself in but hasn't linked its node yet
+    Inconsistent,
+}
+
+impl fmt::Display for TryPopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryPopError::Empty => f.pad("queue is empty"),
+            TryPopError::Inconsistent => f.pad("a push is in progress"),
+        }
+    }
+}
+
+impl error::Error for TryPopError {}
+
+//the owned variant is the intrusive one with a node allocated per value
+struct Node<T> {
+    link: Link,
+    value: T,
+}
+
+unsafe impl<T> Linked for Node<T> {
+    unsafe fn link(this: *const Self) -> *const Link {
+        unsafe { ptr::addr_of!((*this).link) }
+    }
+
+    unsafe fn from_link(link: *const Link) -> *const Self {
+        unsafe { link.byte_sub(mem::offset_of!(Node<T>, link)).cast() }
+    }
+}
+
+pub struct Producer<T> {
+    inner: intrusive::Producer<Node<T>>,
+}
+
+pub struct Consumer<T> {
+    inner: intrusive::Consumer<Node<T>>,
+}
+
+pub fn queue<T>() -> (Producer<T>, Consumer<T>) {
+    let (p, c) = intrusive::queue();
+    (Producer { inner: p }, Consumer { inner: c })
+}
+
+impl<T> Producer<T> {
+    pub fn push(&self, value: T) {
+        self.inner.push(Box::new(Node {
+            link: Link::new(),
+            value,
+        }));
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Producer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn try_pop(&self) -> Result<T, TryPopError> {
+        self.inner.try_pop().map(|node| node.value)
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.inner.pop().map(|node| node.value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
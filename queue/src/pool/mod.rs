@@ -0,0 +1,241 @@
+mod scope;
+mod sleep;
+
+use crate::SegQueue;
+use crate::deque::{Steal, Stealer, Worker};
+use crate::util::random;
+use core::any::Any;
+use core::cell::Cell;
+use core::fmt;
+use core::ptr;
+use sleep::Sleep;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+pub use scope::Scope;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct Shared {
+    //jobs submitted from outside the pool
+    injector: SegQueue<Job>,
+    stealers: Vec<Stealer<Job>>,
+    sleep: Sleep,
+    shutdown: AtomicBool,
+    //first panic of a job started with `spawn`, re-raised by `shutdown`
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl Shared {
+    //workers push to their own deque, everyone else goes through the injector
+    fn push(&self, job: Job) {
+        match WorkerCtx::current(self) {
+            Some(ctx) => ctx.local.push(job),
+            None => self.injector.push(job),
+        }
+        self.sleep.notify_one();
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<*const WorkerCtx> = const { Cell::new(ptr::null()) };
+}
+
+//state of a worker thread, lives on its stack for as long as the thread runs
+struct WorkerCtx {
+    shared: Arc<Shared>,
+    index: usize,
+    local: Worker<Job>,
+}
+
+impl WorkerCtx {
+    //the worker running on this thread, if it belongs to the pool of `shared`
+    fn current<'a>(shared: &Shared) -> Option<&'a WorkerCtx> {
+        let ctx = CURRENT.with(Cell::get);
+        if ctx.is_null() {
+            return None;
+        }
+        let ctx = unsafe { &*ctx };
+        ptr::eq(&*ctx.shared, shared).then_some(ctx)
+    }
+
+    fn find_job(&self) -> Option<Job> {
+        if let Some(job) = self.local.pop() {
+            return Some(job);
+        }
+        loop {
+            if let Some(job) = self.shared.injector.pop() {
+                return Some(job);
+            }
+
+            let stealers = &self.shared.stealers;
+            let start = random(stealers.len());
+            let mut retry = false;
+            for i in 0..stealers.len() {
+                let victim = (start + i) % stealers.len();
+                if victim == self.index {
+                    continue;
+                }
+                match stealers[victim].steal() {
+                    Steal::Success(job) => return Some(job),
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    fn execute(&self, job: Job) {
+        if let Err(p) = panic::catch_unwind(AssertUnwindSafe(job)) {
+            self.shared.panic.lock().unwrap().get_or_insert(p);
+        }
+    }
+
+    fn run(&self) {
+        loop {
+            if let Some(job) = self.find_job() {
+                self.execute(job);
+                continue;
+            }
+
+            let ticket = self.shared.sleep.ticket();
+            if let Some(job) = self.find_job() {
+                self.execute(job);
+                continue;
+            }
+            //queued jobs still run after a shutdown, we only leave once there are none
+            if self.shared.shutdown.load(Ordering::SeqCst) {
+                return;
+            }
+            self.shared.sleep.sleep(ticket);
+        }
+    }
+}
+
+//fixed set of worker threads with a local deque each. idle workers steal
+//from the injector and from each other, and park when there is nothing to do
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "thread pool needs at least one thread");
+
+        let workers: Vec<Worker<Job>> = (0..threads).map(|_| Worker::new_lifo()).collect();
+        let shared = Arc::new(Shared {
+            injector: SegQueue::new(),
+            stealers: workers.iter().map(Worker::stealer).collect(),
+            sleep: Sleep::new(),
+            shutdown: AtomicBool::new(false),
+            panic: Mutex::new(None),
+        });
+
+        let handles = workers
+            .into_iter()
+            .enumerate()
+            .map(|(index, local)| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("pool-worker-{}", index))
+                    .spawn(move || {
+                        let ctx = WorkerCtx {
+                            shared,
+                            index,
+                            local,
+                        };
+                        CURRENT.with(|c| c.set(&ctx));
+                        ctx.run();
+                        CURRENT.with(|c| c.set(ptr::null()));
+                    })
+                    .expect("failed to spawn pool worker")
+            })
+            .collect();
+
+        ThreadPool { shared, handles }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.handles.len()
+    }
+
+    //runs `f` on some worker. a panic in `f` doesn't take the worker down,
+    //it is kept and re-raised by `shutdown`
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.shared.push(Box::new(f));
+    }
+
+    //runs `f` with a scope whose jobs may borrow from the caller and waits for all of them.
+    //a panic in `f` or in any of the jobs is re-raised here once everything finished
+    pub fn scope<'scope, F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'scope>) -> R,
+    {
+        let scope = Scope::new(self.shared.clone());
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.wait();
+        match (res, scope.take_panic()) {
+            (Err(p), _) | (Ok(_), Some(p)) => panic::resume_unwind(p),
+            (Ok(r), None) => r,
+        }
+    }
+
+    //runs `a` on this thread and `b` on the pool. a worker calling this keeps running
+    //jobs while it waits, so it may end up running `b` itself, any other thread just
+    //blocks until a worker got to `b`
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB + Send,
+        RB: Send,
+    {
+        let mut rb = None;
+        let ra = self.scope(|s| {
+            s.spawn(|_| rb = Some(b()));
+            a()
+        });
+        (ra, rb.unwrap())
+    }
+
+    fn stop(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.sleep.notify_all();
+        for h in self.handles.drain(..) {
+            let _ = h.join();
+        }
+        self.shared.panic.lock().unwrap().take()
+    }
+
+    //lets the workers finish every queued job, then joins them.
+    //re-raises the first panic of a job started with `spawn`
+    pub fn shutdown(mut self) {
+        if let Some(p) = self.stop() {
+            panic::resume_unwind(p);
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        if !self.handles.is_empty() {
+            drop(self.stop());
+        }
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("threads", &self.handles.len())
+            .finish()
+    }
+}
@@ -0,0 +1,84 @@
+use super::{Job, Shared, WorkerCtx};
+use core::any::Any;
+use core::marker::PhantomData;
+use core::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+//jobs spawned here may borrow from the stack frame that called `ThreadPool::scope`,
+//which doesn't return before all of them are done
+pub struct Scope<'scope> {
+    shared: Arc<Shared>,
+    pending: AtomicUsize,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+    owner: Thread,
+    //invariant, so a scope can't be passed off as one with a shorter lifetime
+    _marker: PhantomData<&'scope mut &'scope ()>,
+}
+
+//the job only carries the address, the scope outlives every job it spawned
+struct ScopePtr<'scope>(*const Scope<'scope>);
+
+unsafe impl Send for ScopePtr<'_> {}
+
+impl<'scope> Scope<'scope> {
+    pub(super) fn new(shared: Arc<Shared>) -> Self {
+        Scope {
+            shared,
+            pending: AtomicUsize::new(0),
+            panic: Mutex::new(None),
+            owner: thread::current(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce(&Scope<'scope>) + Send + 'scope,
+    {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let ptr = ScopePtr(self);
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            let ptr = ptr;
+            let scope = unsafe { &*ptr.0 };
+            if let Err(p) = panic::catch_unwind(AssertUnwindSafe(|| f(scope))) {
+                scope.panic.lock().unwrap().get_or_insert(p);
+            }
+            scope.complete();
+        });
+        //`wait` keeps everything 'scope alive until the job has run
+        let job: Job = unsafe { mem::transmute(job) };
+        self.shared.push(job);
+    }
+
+    fn complete(&self) {
+        //once `pending` hits zero the scope may be gone, grab the owner first
+        let owner = self.owner.clone();
+        if self.pending.fetch_sub(1, Ordering::AcqRel) == 1 {
+            owner.unpark();
+        }
+    }
+
+    //blocks until every spawned job finished. a worker thread of the same
+    //pool keeps running jobs meanwhile, so nested scopes can't starve the pool
+    pub(super) fn wait(&self) {
+        let ctx = WorkerCtx::current(&self.shared);
+        while self.pending.load(Ordering::Acquire) != 0 {
+            match ctx {
+                Some(ctx) => match ctx.find_job() {
+                    Some(job) => ctx.execute(job),
+                    //new jobs don't unpark us, only our own completions do
+                    None => thread::park_timeout(Duration::from_millis(1)),
+                },
+                None => thread::park(),
+            }
+        }
+    }
+
+    pub(super) fn take_panic(&self) -> Option<Box<dyn Any + Send>> {
+        self.panic.lock().unwrap().take()
+    }
+}
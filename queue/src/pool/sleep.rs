@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+//parking lot for idle workers. a worker takes a ticket, looks for work once
+//more and only sleeps if no new work was announced since the ticket
+pub(super) struct Sleep {
+    events: AtomicUsize,
+    sleepers: AtomicUsize,
+    lock: Mutex<()>,
+    cvar: Condvar,
+}
+
+impl Sleep {
+    pub(super) fn new() -> Self {
+        Sleep {
+            events: AtomicUsize::new(0),
+            sleepers: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cvar: Condvar::new(),
+        }
+    }
+
+    pub(super) fn ticket(&self) -> usize {
+        self.events.load(Ordering::SeqCst)
+    }
+
+    pub(super) fn sleep(&self, ticket: usize) {
+        let guard = self.lock.lock().unwrap();
+        //SeqCst on both sides: either we see the new event or the notifier sees us
+        self.sleepers.fetch_add(1, Ordering::SeqCst);
+        if self.events.load(Ordering::SeqCst) == ticket {
+            drop(self.cvar.wait(guard).unwrap());
+        }
+        self.sleepers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub(super) fn notify_one(&self) {
+        self.events.fetch_add(1, Ordering::SeqCst);
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap();
+            self.cvar.notify_one();
+        }
+    }
+
+    pub(super) fn notify_all(&self) {
+        self.events.fetch_add(1, Ordering::SeqCst);
+        let _guard = self.lock.lock().unwrap();
+        self.cvar.notify_all();
+    }
+}
@@ -0,0 +1,371 @@
+use crate::util::{random, random_bits};
+use core::fmt;
+use crossbeam::epoch::{self, Atomic, Guard, Owned, Shared};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+const MAX_HEIGHT: usize = 20;
+
+//a tower of links, a set tag on `next[level]` marks the node as deleted on that level
+struct Node<P, V> {
+    priority: P,
+    //breaks ties, equal priorities come out in push order
+    seq: u64,
+    //stays in the node until it is destroyed, so an `Entry` can read it after a pop
+    value: V,
+    //set by the pop that claimed the node
+    taken: AtomicBool,
+    //levels the node is linked on, plus one while its push is still building the tower.
+    //whoever drops it to zero retires the node
+    refs: AtomicUsize,
+    next: Box<[Atomic<Node<P, V>>]>,
+}
+
+impl<P: Ord, V> Node<P, V> {
+    fn before(&self, priority: &P, seq: u64) -> bool {
+        (&self.priority, self.seq) < (priority, seq)
+    }
+
+    fn claim(&self) -> bool {
+        !self.taken.load(Ordering::Relaxed)
+            && self
+                .taken
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    unsafe fn release(node: Shared<'_, Self>, guard: &Guard) {
+        unsafe {
+            if node.deref().refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+                guard.defer_destroy(node);
+            }
+        }
+    }
+}
+
+//predecessor link and successor on every level, around some key
+struct Position<'g, P, V> {
+    preds: [&'g Atomic<Node<P, V>>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<P, V>>; MAX_HEIGHT],
+}
+
+fn random_height() -> usize {
+    1 + (random_bits().trailing_ones() as usize).min(MAX_HEIGHT - 1)
+}
+
+//lock-free skiplist ordered by priority, smallest first. pops claim a node
+//with its `taken` flag, then mark its links and let traversals unlink it.
+//nodes are reclaimed through crossbeam epochs
+pub struct PriorityQueue<P, V> {
+    head: [Atomic<Node<P, V>>; MAX_HEIGHT],
+    seq: AtomicU64,
+    //counts pushes that started and pops that finished, so it never underflows
+    len: AtomicUsize,
+}
+
+//the smallest element at the time of `peek_min`. it may be popped while the
+//entry is alive, the guard keeps the node and its value around until then
+pub struct Entry<'g, P, V> {
+    node: &'g Node<P, V>,
+}
+
+impl<P, V> Entry<'_, P, V> {
+    pub fn priority(&self) -> &P {
+        &self.node.priority
+    }
+
+    pub fn value(&self) -> &V {
+        &self.node.value
+    }
+}
+
+impl<P: fmt::Debug, V: fmt::Debug> fmt::Debug for Entry<'_, P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("priority", self.priority())
+            .field("value", self.value())
+            .finish()
+    }
+}
+
+impl<P: Ord, V> PriorityQueue<P, V> {
+    pub fn new() -> Self {
+        PriorityQueue {
+            head: Default::default(),
+            seq: AtomicU64::new(0),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //finds the links around `(priority, seq)` on every level and unlinks
+    //marked nodes on the way
+    fn find<'g>(&'g self, priority: &P, seq: u64, guard: &'g Guard) -> Position<'g, P, V> {
+        'retry: loop {
+            let mut pos = Position {
+                preds: [&self.head[0]; MAX_HEIGHT],
+                succs: [Shared::null(); MAX_HEIGHT],
+            };
+            let mut tower: &'g [Atomic<Node<P, V>>] = &self.head;
+
+            for level in (0..MAX_HEIGHT).rev() {
+                let mut curr = tower[level].load(Ordering::Acquire, guard);
+                loop {
+                    //our predecessor got deleted, its links can't be used anymore
+                    if curr.tag() == 1 {
+                        continue 'retry;
+                    }
+                    let Some(c) = (unsafe { curr.as_ref() }) else {
+                        break;
+                    };
+
+                    let succ = c.next[level].load(Ordering::Acquire, guard);
+                    if succ.tag() == 1 {
+                        match tower[level].compare_exchange(
+                            curr,
+                            succ.with_tag(0),
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            guard,
+                        ) {
+                            Ok(_) => {
+                                unsafe { Node::release(curr, guard) };
+                                curr = succ.with_tag(0);
+                                continue;
+                            }
+                            Err(_) => continue 'retry,
+                        }
+                    }
+
+                    if c.before(priority, seq) {
+                        tower = &c.next;
+                        curr = succ;
+                    } else {
+                        break;
+                    }
+                }
+                pos.preds[level] = &tower[level];
+                pos.succs[level] = curr;
+            }
+            return pos;
+        }
+    }
+
+    pub fn push(&self, priority: P, value: V) {
+        let guard = &epoch::pin();
+        let height = random_height();
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let node = Owned::new(Node {
+            priority,
+            seq,
+            value,
+            taken: AtomicBool::new(false),
+            refs: AtomicUsize::new(1),
+            next: (0..height).map(|_| Atomic::null()).collect(),
+        })
+        .into_shared(guard);
+        let n = unsafe { node.deref() };
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        //the bottom level decides membership
+        let mut pos;
+        loop {
+            pos = self.find(&n.priority, seq, guard);
+            n.next[0].store(pos.succs[0], Ordering::Relaxed);
+            //count the link before it exists, a snip may follow right after the cas
+            n.refs.fetch_add(1, Ordering::Relaxed);
+            if pos.preds[0]
+                .compare_exchange(
+                    pos.succs[0],
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    guard,
+                )
+                .is_ok()
+            {
+                break;
+            }
+            n.refs.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        'build: for level in 1..height {
+            loop {
+                //a pop already marked this level, stop building
+                let next = n.next[level].load(Ordering::Acquire, guard);
+                if next.tag() == 1
+                    || n.next[level]
+                        .compare_exchange(
+                            next,
+                            pos.succs[level],
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            guard,
+                        )
+                        .is_err()
+                {
+                    break 'build;
+                }
+
+                n.refs.fetch_add(1, Ordering::Relaxed);
+                if pos.preds[level]
+                    .compare_exchange(
+                        pos.succs[level],
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        guard,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+                n.refs.fetch_sub(1, Ordering::Relaxed);
+                pos = self.find(&n.priority, seq, guard);
+            }
+        }
+        unsafe { Node::release(node, guard) };
+    }
+
+    //copies the element out of a node this thread claimed and unlinks the node.
+    //the original goes with the node, `Entry`s may still be reading it
+    unsafe fn take(&self, node: Shared<'_, Node<P, V>>, guard: &Guard) -> (P, V)
+    where
+        P: Clone,
+        V: Clone,
+    {
+        let n = unsafe { node.deref() };
+        let value = n.value.clone();
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        for level in (0..n.next.len()).rev() {
+            let mut next = n.next[level].load(Ordering::Acquire, guard);
+            while next.tag() == 0 {
+                match n.next[level].compare_exchange(
+                    next,
+                    next.with_tag(1),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                ) {
+                    Ok(_) => break,
+                    Err(e) => next = e.current,
+                }
+            }
+        }
+        //unlinks it from every level it is on right now
+        self.find(&n.priority, n.seq, guard);
+
+        (n.priority.clone(), value)
+    }
+
+    //claims the first unclaimed node from `curr` on along the bottom level
+    fn pop_from<'g>(&'g self, mut curr: Shared<'g, Node<P, V>>, guard: &'g Guard) -> Option<(P, V)>
+    where
+        P: Clone,
+        V: Clone,
+    {
+        while let Some(c) = unsafe { curr.as_ref() } {
+            if c.claim() {
+                return Some(unsafe { self.take(curr, guard) });
+            }
+            curr = c.next[0].load(Ordering::Acquire, guard).with_tag(0);
+        }
+        None
+    }
+
+    pub fn pop_min(&self) -> Option<(P, V)>
+    where
+        P: Clone,
+        V: Clone,
+    {
+        let guard = &epoch::pin();
+        self.pop_from(self.head[0].load(Ordering::Acquire, guard), guard)
+    }
+
+    //spraylist-style relaxed pop: takes one of roughly the `spread` smallest
+    //elements instead of all threads fighting over the minimum. `spread`
+    //is usually the number of threads popping concurrently
+    pub fn pop_approx_min(&self, spread: usize) -> Option<(P, V)>
+    where
+        P: Clone,
+        V: Clone,
+    {
+        let guard = &epoch::pin();
+        let levels = (usize::BITS - spread.max(1).leading_zeros()) as usize;
+        let top = levels.min(MAX_HEIGHT) - 1;
+
+        //random walk down from `top`, a few hops per level
+        let mut tower: &[Atomic<Node<P, V>>] = &self.head;
+        let mut landed = Shared::null();
+        for level in (0..=top).rev() {
+            for _ in 0..random(levels + 1) {
+                let next = tower[level].load(Ordering::Acquire, guard).with_tag(0);
+                let Some(n) = (unsafe { next.as_ref() }) else {
+                    break;
+                };
+                landed = next;
+                tower = &n.next;
+            }
+        }
+
+        let start = if landed.is_null() {
+            self.head[0].load(Ordering::Acquire, guard)
+        } else {
+            landed
+        };
+        //landed past everything that is left, fall back to the front
+        self.pop_from(start, guard)
+            .or_else(|| self.pop_from(self.head[0].load(Ordering::Acquire, guard), guard))
+    }
+
+    pub fn peek_min<'g>(&'g self, guard: &'g Guard) -> Option<Entry<'g, P, V>> {
+        let mut curr = self.head[0].load(Ordering::Acquire, guard);
+        while let Some(c) = unsafe { curr.as_ref() } {
+            if !c.taken.load(Ordering::Acquire) {
+                return Some(Entry { node: c });
+            }
+            curr = c.next[0].load(Ordering::Acquire, guard).with_tag(0);
+        }
+        None
+    }
+}
+
+impl<P: Ord, V> Default for PriorityQueue<P, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, V> Drop for PriorityQueue<P, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = epoch::unprotected();
+            //a node can be left on upper levels only, collect it from all of them
+            let mut nodes = HashSet::new();
+            for level in 0..MAX_HEIGHT {
+                let mut curr = self.head[level].load(Ordering::Relaxed, guard);
+                while let Some(c) = curr.as_ref() {
+                    nodes.insert(curr.as_raw());
+                    curr = c.next[level].load(Ordering::Relaxed, guard).with_tag(0);
+                }
+            }
+            for p in nodes {
+                drop(Owned::from_raw(p as *mut Node<P, V>));
+            }
+        }
+    }
+}
+
+impl<P, V> fmt::Debug for PriorityQueue<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PriorityQueue { .. }")
+    }
+}
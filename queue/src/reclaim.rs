@@ -0,0 +1,64 @@
+use crate::hazard::{self, HazardPointer};
+use crossbeam::epoch;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+//memory reclamation scheme for the lock-free structures in this crate.
+//a guard lives for one operation and can protect up to four pointers at a time, in slots 0..4
+pub trait Reclaim {
+    type Guard;
+
+    fn guard() -> Self::Guard;
+
+    //loads `src` and keeps the target alive while it stays in `slot`
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T;
+
+    /// Frees `ptr` once no guard can observe it anymore.
+    ///
+    /// # Safety
+    /// `ptr` must come from `Box::into_raw`, be retired only once and already be unlinked.
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T);
+}
+
+//crossbeam epochs: cheap protection, but one stalled thread holds back all frees
+pub struct Epoch;
+
+//hazard pointers: validated loads, at most a bounded number of nodes left unfreed
+pub struct Hazard;
+
+impl Reclaim for Epoch {
+    type Guard = epoch::Guard;
+
+    #[inline(always)]
+    fn guard() -> Self::Guard {
+        epoch::pin()
+    }
+
+    #[inline(always)]
+    fn protect<T>(_: &mut Self::Guard, _: usize, src: &AtomicPtr<T>) -> *mut T {
+        src.load(Ordering::Acquire)
+    }
+
+    #[inline(always)]
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T) {
+        unsafe { guard.defer_unchecked(move || drop(Box::from_raw(ptr))) }
+    }
+}
+
+impl Reclaim for Hazard {
+    type Guard = [HazardPointer; 4];
+
+    #[inline(always)]
+    fn guard() -> Self::Guard {
+        core::array::from_fn(|_| HazardPointer::new())
+    }
+
+    #[inline(always)]
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        guard[slot].protect(src)
+    }
+
+    #[inline(always)]
+    unsafe fn retire<T>(_: &Self::Guard, ptr: *mut T) {
+        unsafe { hazard::retire(ptr) }
+    }
+}
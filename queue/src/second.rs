@@ -1,179 +1,328 @@
-use core::cell::RefCell;
+use crate::reclaim::{Epoch, Reclaim};
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
-use crossbeam::epoch::{self, Atomic, Guard, Owned, Shared};
+use core::ptr;
 use crossbeam::utils::CachePadded;
-use std::sync::atomic::Ordering;
-
-thread_local! {
-    static THREAD_GUARD: RefCell<Option<Guard>> = const{RefCell::new(None)};
-}
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 #[repr(align(64))]
-pub struct AtomicQueue<T> {
+pub struct AtomicQueue<T, R: Reclaim = Epoch> {
     head: CachePadded<Link<T>>,
     tail: CachePadded<Link<T>>,
 
-    _marker: PhantomData<T>,
+    _marker: PhantomData<(T, R)>,
 }
 
-type Link<T> = Atomic<Node<T>>;
+type Link<T> = AtomicPtr<Node<T>>;
 struct Node<T> {
     data: MaybeUninit<T>,
     next: Link<T>,
 }
 
 impl<T> Node<T> {
-    fn dummy() -> Self {
-        Node {
-            data: MaybeUninit::uninit(),
-            next: Atomic::null(),
-        }
+    fn dummy() -> *mut Self {
+        Self::alloc(MaybeUninit::uninit())
+    }
+
+    fn alloc(data: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            data,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
     }
 }
 
+//guard slots used by the operations below
+const HEAD: usize = 0;
+const NEXT: usize = 1;
+const WALK: usize = 2;
+const TAIL: usize = 3;
+
 impl<T> AtomicQueue<T> {
     pub fn new() -> Self {
-        let dummy = CachePadded::new(Atomic::new(Node::dummy()));
+        Self::with_reclaim()
+    }
+}
+
+impl<T, R: Reclaim> AtomicQueue<T, R> {
+    //`AtomicQueue::<T, Hazard>::with_reclaim()` picks the reclamation scheme
+    pub fn with_reclaim() -> Self {
+        let dummy = Node::dummy();
         AtomicQueue {
-            head: dummy.clone(),
-            tail: dummy,
+            head: CachePadded::new(AtomicPtr::new(dummy)),
+            tail: CachePadded::new(AtomicPtr::new(dummy)),
             _marker: PhantomData,
         }
     }
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        THREAD_GUARD.with(|cell| {
-            let mut guard_ref = cell.borrow_mut();
-
-            if guard_ref.is_none() {
-                *guard_ref = Some(epoch::pin());
-            }
-            let g = guard_ref.as_ref().unwrap();
-            let head = self.head.load(Ordering::Acquire, g);
-            let next = unsafe { head.as_ref().unwrap().next.load(Ordering::Acquire, g) };
-            next.is_null()
-        })
+        let g = &mut R::guard();
+        let head = R::protect(g, HEAD, &self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
     }
 
     #[inline(always)]
     pub fn enqueue(&self, data: T) {
-        THREAD_GUARD.with(|cell| {
-            let mut guard_ref = cell.borrow_mut();
+        let new_node = Node::alloc(MaybeUninit::new(data));
+        self.link(new_node, new_node);
+    }
 
-            if guard_ref.is_none() {
-                *guard_ref = Some(epoch::pin());
-            }
-            let g = guard_ref.as_ref().unwrap();
-            let new_node = Owned::new(Node {
-                data: MaybeUninit::new(data),
-                next: Atomic::null(),
-            })
-            .into_shared(g);
-
-            loop {
-                let tail = self.tail.load(Ordering::Acquire, g);
-                let tail_ref = unsafe { tail.as_ref().unwrap() };
-                let next = tail_ref.next.load(Ordering::Acquire, g);
+    //appends the already linked chain `first..=last` after the current tail
+    fn link(&self, first: *mut Node<T>, last: *mut Node<T>) {
+        let g = &mut R::guard();
+        loop {
+            let tail = R::protect(g, TAIL, &self.tail);
+            let tail_ref = unsafe { &*tail };
+            let next = tail_ref.next.load(Ordering::Acquire);
 
-                if next.is_null() {
-                    let success = tail_ref
-                        .next
-                        .compare_exchange(next, new_node, Ordering::Release, Ordering::Relaxed, g)
-                        .is_ok();
-
-                    if success {
-                        //advance the tail, tail might still be lagging
-                        let _ = self.tail.compare_exchange(
-                            tail,
-                            new_node,
-                            Ordering::Relaxed,
-                            Ordering::Relaxed,
-                            g,
-                        );
-                        break;
-                    }
-                } else {
-                    //tail is lagging, advance
+            if next.is_null() {
+                let success = tail_ref
+                    .next
+                    .compare_exchange(next, first, Ordering::Release, Ordering::Relaxed)
+                    .is_ok();
+
+                if success {
+                    //advance the tail, tail might still be lagging
                     let _ = self.tail.compare_exchange(
                         tail,
-                        next,
+                        last,
+                        Ordering::Release,
                         Ordering::Relaxed,
-                        Ordering::Relaxed,
-                        g,
                     );
+                    break;
                 }
+            } else {
+                //tail is lagging, advance
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
             }
-        })
+        }
     }
 
     #[inline(always)]
     pub fn dequeue(&self) -> Option<T> {
-        THREAD_GUARD.with(|cell| {
-            let mut guard_ref = cell.borrow_mut();
-
-            if guard_ref.is_none() {
-                *guard_ref = Some(epoch::pin());
+        let g = &mut R::guard();
+        loop {
+            let head = R::protect(g, HEAD, &self.head);
+            let next = R::protect(g, NEXT, unsafe { &(*head).next });
+            //head moved on while we were protecting next, next may already be gone
+            if self.head.load(Ordering::Acquire) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
             }
-            let g = guard_ref.as_ref().unwrap();
-            loop {
-                let head = self.head.load(Ordering::Acquire, g);
-                let head_ref = unsafe { head.as_ref().unwrap() };
+            //never let head pass a lagging tail, or the retired dummy would stay reachable
+            let tail = self.tail.load(Ordering::Acquire);
+            if tail == head {
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                //next becomes the new dummy, drop the previous one
+                let val = unsafe { Self::take_data(next) };
+                unsafe { R::retire(g, head) };
+                return Some(val);
+            }
+        }
+    }
+
+    //links the items into a private chain first, so the whole batch
+    //is published with a single CAS on the tail node
+    pub fn enqueue_batch<I: IntoIterator<Item = T>>(&self, items: I) {
+        let mut items = items.into_iter();
+        let Some(first) = items.next() else {
+            return;
+        };
+
+        let first = Node::alloc(MaybeUninit::new(first));
+        let mut last = first;
+        for data in items {
+            let node = Node::alloc(MaybeUninit::new(data));
+            //nobody else can see the chain yet
+            unsafe { (*last).next.store(node, Ordering::Relaxed) };
+            last = node;
+        }
+        self.link(first, last);
+    }
+
+    //moves up to `max` items into `buf`, detaching them from the head with a single CAS.
+    //returns how many were taken
+    pub fn dequeue_batch(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        if max == 0 {
+            return 0;
+        }
 
-                let next = head_ref.next.load(Ordering::Acquire, g);
+        let g = &mut R::guard();
+        'retry: loop {
+            let head = R::protect(g, HEAD, &self.head);
+
+            //find the last node of the batch, it becomes the new dummy.
+            //walks hand over hand, nothing past an unchanged head can be retired
+            let mut last = head;
+            let mut slot = NEXT;
+            let mut n = 0;
+            while n < max {
+                let next = R::protect(g, slot, unsafe { &(*last).next });
+                if self.head.load(Ordering::Acquire) != head {
+                    continue 'retry;
+                }
                 if next.is_null() {
-                    return None;
+                    break;
                 }
-                if self
-                    .head
-                    .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed, g)
-                    .is_ok()
-                {
-                    //drop previous dummy
-                    let val = Self::take_data(next);
-                    unsafe { g.defer_destroy(head) };
-                    return Some(val);
+                last = next;
+                slot = if slot == NEXT { WALK } else { NEXT };
+                n += 1;
+            }
+            if n == 0 {
+                return 0;
+            }
+            self.catch_up_tail(g);
+
+            if self
+                .head
+                .compare_exchange(head, last, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                buf.reserve(n);
+                let mut node = head;
+                for _ in 0..n {
+                    let next = unsafe { (*node).next.load(Ordering::Acquire) };
+                    buf.push(unsafe { Self::take_data(next) });
+                    unsafe { R::retire(g, node) };
+                    node = next;
                 }
+                return n;
             }
-        })
+        }
+    }
+
+    //moves the tail to the actual last node
+    fn catch_up_tail(&self, g: &mut R::Guard) {
+        loop {
+            let tail = R::protect(g, TAIL, &self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                return;
+            }
+            let _ = self
+                .tail
+                .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+        }
+    }
+
+    //dequeues until the queue is observed empty, items pushed
+    //concurrently may or may not be picked up
+    pub fn try_iter(&self) -> TryIter<'_, T, R> {
+        TryIter { queue: self }
+    }
+
+    //like `try_iter`, but anything left when the iterator is dropped is dequeued and dropped too
+    pub fn drain(&self) -> Drain<'_, T, R> {
+        Drain { queue: self }
     }
 
     #[inline(always)]
-    fn take_data(ptr: Shared<'_, Node<T>>) -> T {
-        unsafe { ptr.as_ref().unwrap().data.assume_init_read() }
+    unsafe fn take_data(ptr: *mut Node<T>) -> T {
+        unsafe { (*ptr).data.assume_init_read() }
     }
 }
 
-unsafe impl<T: Send> Sync for AtomicQueue<T> {}
-unsafe impl<T: Send> Send for AtomicQueue<T> {}
+unsafe impl<T: Send, R: Reclaim> Sync for AtomicQueue<T, R> {}
+unsafe impl<T: Send, R: Reclaim> Send for AtomicQueue<T, R> {}
 
-impl<T> Default for AtomicQueue<T> {
+impl<T, R: Reclaim> Default for AtomicQueue<T, R> {
     fn default() -> Self {
-        Self::new()
+        Self::with_reclaim()
     }
 }
 
-impl<T> Drop for AtomicQueue<T> {
+impl<T, R: Reclaim> Drop for AtomicQueue<T, R> {
     fn drop(&mut self) {
-        let g = &epoch::pin();
-        let mut head = self.head.load(Ordering::Acquire, g);
+        //no other thread can reach the nodes anymore, free them right away
+        let mut node = *self.head.get_mut();
+        let mut dummy = true;
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            if !dummy {
+                unsafe { boxed.data.assume_init_drop() };
+            }
+            node = *boxed.next.get_mut();
+            dummy = false;
+        }
+    }
+}
 
-        let mut c = 0;
-        while !head.is_null() {
-            unsafe {
-                let node_ref = head.deref_mut();
-                let next = node_ref.next.load(Ordering::Acquire, g);
+impl<T, R: Reclaim> FromIterator<T> for AtomicQueue<T, R> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let q = AtomicQueue::with_reclaim();
+        q.enqueue_batch(iter);
+        q
+    }
+}
 
-                if c > 0 {
-                    node_ref.data.assume_init_drop();
-                }
+impl<T, R: Reclaim> Extend<T> for AtomicQueue<T, R> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.enqueue_batch(iter);
+    }
+}
 
-                g.defer_destroy(head);
-                head = next;
-                c += 1;
-            }
-        }
+impl<T, R: Reclaim> Extend<T> for &AtomicQueue<T, R> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.enqueue_batch(iter);
+    }
+}
+
+impl<T, R: Reclaim> IntoIterator for AtomicQueue<T, R> {
+    type Item = T;
+    type IntoIter = IntoIter<T, R>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { queue: self }
     }
 }
 
+pub struct IntoIter<T, R: Reclaim = Epoch> {
+    queue: AtomicQueue<T, R>,
+}
+
+impl<T, R: Reclaim> Iterator for IntoIter<T, R> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.queue.dequeue()
+    }
+}
+
+pub struct TryIter<'a, T, R: Reclaim = Epoch> {
+    queue: &'a AtomicQueue<T, R>,
+}
+
+impl<T, R: Reclaim> Iterator for TryIter<'_, T, R> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.queue.dequeue()
+    }
+}
+
+pub struct Drain<'a, T, R: Reclaim = Epoch> {
+    queue: &'a AtomicQueue<T, R>,
+}
+
+impl<T, R: Reclaim> Iterator for Drain<'_, T, R> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.queue.dequeue()
+    }
+}
+
+impl<T, R: Reclaim> Drop for Drain<'_, T, R> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
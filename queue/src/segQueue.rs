@@ -1,3 +1,6 @@
+//adapted from `SegQueue` in crossbeam-queue (https://github.com/crossbeam-rs/crossbeam),
+//Copyright (c) 2019 The Crossbeam Project Developers, used under the MIT license in LICENSE-crossbeam
+
 extern crate alloc;
 
 use alloc::alloc::{alloc_zeroed, handle_alloc_error, Layout};
@@ -65,16 +68,20 @@ impl<T> Block<T> {
         }
     }
 
+    //frees the block once every slot from `start` on has been read,
+    //otherwise leaves a DESTROY mark for the last pending reader
     unsafe fn destroy(this: *mut Block<T>, start: usize) {
-        for i in start..BLOCK_CAP - 1 {
-            let slot = (*this).slots.get_unchecked(i);
-            if slot.state.load(Ordering::Acquire) & READ == 0
-                && slot.state.fetch_or(DESTROY, Ordering::AcqRel) & READ == 0
-            {
-                return;
+        unsafe {
+            for i in start..BLOCK_CAP - 1 {
+                let slot = (*this).slots.get_unchecked(i);
+                if slot.state.load(Ordering::Acquire) & READ == 0
+                    && slot.state.fetch_or(DESTROY, Ordering::AcqRel) & READ == 0
+                {
+                    return;
+                }
             }
+            drop(Box::from_raw(this));
         }
-        drop(Box::from_raw(this));
     }
 }
 
@@ -174,6 +181,8 @@ impl<T> SegQueue<T> {
 
     pub fn pop(&self) -> Option<T> {
         let backoff = Backoff::new();
+        let mut head = self.head.index.load(Ordering::Acquire);
+        let mut block = self.head.block.load(Ordering::Acquire);
 
         loop {
             let offset = (head >> SHIFT) % LAP;
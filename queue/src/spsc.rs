@@ -0,0 +1,212 @@
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+use crossbeam::utils::CachePadded;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//head and tail count up and wrap around, the slot is the index masked to the
+//buffer. the buffer is `cap` rounded up to a power of two so the mask stays
+//right across the wrap
+struct Inner<T> {
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    cap: usize,
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    fn cap(&self) -> usize {
+        self.cap
+    }
+
+    fn slot(&self, i: usize) -> *mut MaybeUninit<T> {
+        self.buffer[i & (self.buffer.len() - 1)].get()
+    }
+
+    //the slots for `[start, start + n)` as at most two contiguous runs
+    fn runs(&self, start: usize, n: usize) -> [(*mut T, usize); 2] {
+        let at = start & (self.buffer.len() - 1);
+        let first = n.min(self.buffer.len() - at);
+        [
+            (self.slot(at) as *mut T, first),
+            (self.slot(0) as *mut T, n - first),
+        ]
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        for i in 0..tail.wrapping_sub(head) {
+            unsafe { (*self.slot(head.wrapping_add(i))).assume_init_drop() };
+        }
+    }
+}
+
+//bounded single-producer single-consumer ring. each side owns one index and
+//keeps a cached copy of the other, so it only touches the other side's cache
+//line when the cached value says full (or empty). every operation is wait-free
+pub fn ring<T>(cap: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(cap > 0, "capacity must be non-zero");
+    let inner = Arc::new(Inner {
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        cap,
+        buffer: (0..cap.next_power_of_two())
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+    });
+    (
+        Producer {
+            inner: inner.clone(),
+            tail: 0,
+            head: 0,
+        },
+        Consumer {
+            inner,
+            head: 0,
+            tail: 0,
+        },
+    )
+}
+
+pub struct Producer<T> {
+    inner: Arc<Inner<T>>,
+    tail: usize,
+    //last head seen, may be behind the real one
+    head: usize,
+}
+
+pub struct Consumer<T> {
+    inner: Arc<Inner<T>>,
+    head: usize,
+    //last tail seen, may be behind the real one
+    tail: usize,
+}
+
+impl<T> Producer<T> {
+    pub fn capacity(&self) -> usize {
+        self.inner.cap()
+    }
+
+    //free slots, refreshing the cached head only if it looks full
+    pub fn slots(&mut self) -> usize {
+        if self.slots_cached() == 0 {
+            self.head = self.inner.head.load(Ordering::Acquire);
+        }
+        self.slots_cached()
+    }
+
+    pub fn is_full(&mut self) -> bool {
+        self.slots() == 0
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.slots() == 0 {
+            return Err(value);
+        }
+        unsafe { (*self.inner.slot(self.tail)).write(value) };
+        self.tail = self.tail.wrapping_add(1);
+        self.inner.tail.store(self.tail, Ordering::Release);
+        Ok(())
+    }
+
+    //copies as much of `src` as fits and publishes it with a single store
+    pub fn write_chunk(&mut self, src: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        if self.slots_cached() < src.len() {
+            self.head = self.inner.head.load(Ordering::Acquire);
+        }
+        let n = src.len().min(self.slots_cached());
+        let mut done = 0;
+        for (dst, len) in self.inner.runs(self.tail, n) {
+            unsafe { ptr::copy_nonoverlapping(src.as_ptr().add(done), dst, len) };
+            done += len;
+        }
+        self.tail = self.tail.wrapping_add(n);
+        self.inner.tail.store(self.tail, Ordering::Release);
+        n
+    }
+
+    fn slots_cached(&self) -> usize {
+        self.inner.cap() - self.tail.wrapping_sub(self.head)
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn capacity(&self) -> usize {
+        self.inner.cap()
+    }
+
+    //items ready to read, refreshing the cached tail only if it looks empty
+    pub fn len(&mut self) -> usize {
+        if self.tail == self.head {
+            self.tail = self.inner.tail.load(Ordering::Acquire);
+        }
+        self.tail.wrapping_sub(self.head)
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let value = unsafe { (*self.inner.slot(self.head)).assume_init_read() };
+        self.head = self.head.wrapping_add(1);
+        self.inner.head.store(self.head, Ordering::Release);
+        Some(value)
+    }
+
+    pub fn peek(&mut self) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(unsafe { (*self.inner.slot(self.head)).assume_init_ref() })
+    }
+
+    //fills as much of `dst` as there are items and frees the slots with a single store
+    pub fn read_chunk(&mut self, dst: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        if self.tail.wrapping_sub(self.head) < dst.len() {
+            self.tail = self.inner.tail.load(Ordering::Acquire);
+        }
+        let n = dst.len().min(self.tail.wrapping_sub(self.head));
+        let mut done = 0;
+        for (src, len) in self.inner.runs(self.head, n) {
+            unsafe { ptr::copy_nonoverlapping(src, dst.as_mut_ptr().add(done), len) };
+            done += len;
+        }
+        self.head = self.head.wrapping_add(n);
+        self.inner.head.store(self.head, Ordering::Release);
+        n
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer")
+            .field("capacity", &self.inner.cap())
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer")
+            .field("capacity", &self.inner.cap())
+            .finish()
+    }
+}
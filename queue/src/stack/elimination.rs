@@ -0,0 +1,64 @@
+use super::Node;
+use crate::util::random;
+use core::ptr;
+use crossbeam::epoch::Owned;
+use crossbeam::utils::{Backoff, CachePadded};
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+//slots where a pusher parks its node for a moment. a popper that finds one
+//takes it, so the pair completes without touching the stack's head.
+//parked nodes are never shared with readers, whoever wins the slot cas owns the node
+pub(super) struct Elimination<T> {
+    slots: Box<[CachePadded<AtomicPtr<Node<T>>>]>,
+}
+
+impl<T> Elimination<T> {
+    pub(super) fn new(width: usize) -> Self {
+        Elimination {
+            slots: (0..width)
+                .map(|_| CachePadded::new(AtomicPtr::new(ptr::null_mut())))
+                .collect(),
+        }
+    }
+
+    fn slot(&self) -> &AtomicPtr<Node<T>> {
+        &self.slots[random(self.slots.len())]
+    }
+
+    //parks `node` in a random slot. Ok if a popper took it, otherwise the node comes back
+    pub(super) fn offer(&self, node: Owned<Node<T>>) -> Result<(), Owned<Node<T>>> {
+        let slot = self.slot();
+        let raw = Box::into_raw(node.into_box());
+        if slot
+            .compare_exchange(ptr::null_mut(), raw, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(unsafe { Owned::from_raw(raw) });
+        }
+
+        let backoff = Backoff::new();
+        while !backoff.is_completed() {
+            if slot.load(Ordering::Relaxed) != raw {
+                return Ok(());
+            }
+            backoff.snooze();
+        }
+
+        //withdraw. if this fails a popper got there first
+        match slot.compare_exchange(raw, ptr::null_mut(), Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => Err(unsafe { Owned::from_raw(raw) }),
+            Err(_) => Ok(()),
+        }
+    }
+
+    pub(super) fn take(&self) -> Option<Owned<Node<T>>> {
+        let slot = self.slot();
+        let p = slot.load(Ordering::Relaxed);
+        if p.is_null() {
+            return None;
+        }
+        slot.compare_exchange(p, ptr::null_mut(), Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| unsafe { Owned::from_raw(p) })
+    }
+}
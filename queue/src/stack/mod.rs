@@ -0,0 +1,191 @@
+mod elimination;
+
+use core::fmt;
+use core::mem::ManuallyDrop;
+use crossbeam::epoch::{self, Atomic, Guard, Owned, Shared};
+use crossbeam::utils::CachePadded;
+use elimination::Elimination;
+use std::sync::atomic::Ordering;
+
+struct Node<T> {
+    data: ManuallyDrop<T>,
+    next: Atomic<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(data: T) -> Self {
+        Node {
+            data: ManuallyDrop::new(data),
+            next: Atomic::null(),
+        }
+    }
+
+    //a node that never made it onto the stack, nobody else can see it
+    fn into_data(node: Owned<Node<T>>) -> T {
+        let mut node = node.into_box();
+        unsafe { ManuallyDrop::take(&mut node.data) }
+    }
+}
+
+//treiber stack, nodes are reclaimed through crossbeam epochs.
+//with an elimination array a push and a pop that collide on `head` can
+//hand the value over directly instead of both retrying the cas
+pub struct Stack<T> {
+    head: CachePadded<Atomic<Node<T>>>,
+    elimination: Option<Elimination<T>>,
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack {
+            head: CachePadded::new(Atomic::null()),
+            elimination: None,
+        }
+    }
+
+    //`width` slots for colliding push/pop pairs, around half the number of
+    //threads hammering the stack is a good start
+    pub fn with_elimination(width: usize) -> Self {
+        assert!(width > 0, "elimination width must be non-zero");
+        Stack {
+            head: CachePadded::new(Atomic::null()),
+            elimination: Some(Elimination::new(width)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = &epoch::pin();
+        self.head.load(Ordering::Acquire, guard).is_null()
+    }
+
+    pub fn push(&self, value: T) {
+        let mut node = Owned::new(Node::new(value));
+        let guard = &epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Relaxed, guard);
+            node.next.store(head, Ordering::Relaxed);
+            match self.head.compare_exchange(
+                head,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(_) => return,
+                Err(e) => node = e.new,
+            }
+
+            //lost the race on `head`, see if a popper wants the value directly
+            if let Some(elimination) = &self.elimination {
+                match elimination.offer(node) {
+                    Ok(()) => return,
+                    Err(n) => node = n,
+                }
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = &epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Acquire, guard);
+            let next = unsafe { head.as_ref() }?
+                .next
+                .load(Ordering::Relaxed, guard);
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed, guard)
+                .is_ok()
+            {
+                return Some(unsafe { Self::take(head, guard) });
+            }
+
+            if let Some(elimination) = &self.elimination
+                && let Some(node) = elimination.take()
+            {
+                return Some(Node::into_data(node));
+            }
+        }
+    }
+
+    //moves the data out of a node this thread just unlinked and retires the node.
+    //the node itself stays readable until every guard pinned before the unlink is gone
+    unsafe fn take(node: Shared<'_, Node<T>>, guard: &Guard) -> T {
+        unsafe {
+            let data = ManuallyDrop::into_inner(core::ptr::read(&node.deref().data));
+            guard.defer_destroy(node);
+            data
+        }
+    }
+
+    //runs `f` on the top element without removing it. pops don't wait for `f`,
+    //so the element may be popped and dropped meanwhile. the guard keeps the
+    //node alive, and `T: Copy` means that drop freed nothing `f` could reach
+    pub fn peek_with<F, R>(&self, f: F) -> Option<R>
+    where
+        T: Copy,
+        F: FnOnce(&T) -> R,
+    {
+        let guard = &epoch::pin();
+        let head = self.head.load(Ordering::Acquire, guard);
+        unsafe { head.as_ref() }.map(|node| f(&node.data))
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut cur = self.head.load(Ordering::Relaxed, guard);
+            while let Some(node) = cur.as_ref() {
+                let next = node.next.load(Ordering::Relaxed, guard);
+                let mut owned = cur.into_owned();
+                ManuallyDrop::drop(&mut owned.data);
+                cur = next;
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Stack { .. }")
+    }
+}
+
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let s = Stack::new();
+        iter.into_iter().for_each(|v| s.push(v));
+        s
+    }
+}
+
+//yields the elements top first
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { stack: self }
+    }
+}
+
+pub struct IntoIter<T> {
+    stack: Stack<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.stack.pop()
+    }
+}
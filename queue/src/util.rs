@@ -0,0 +1,23 @@
+use core::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+thread_local! {
+    static RNG: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+}
+
+//xorshift, only used to spread threads over slots, never for anything that needs real randomness
+pub(crate) fn random_bits() -> u64 {
+    RNG.with(|rng| {
+        let mut x = rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        rng.set(x);
+        x
+    })
+}
+
+pub(crate) fn random(n: usize) -> usize {
+    (random_bits() % n as u64) as usize
+}
@@ -0,0 +1,105 @@
+use queue::ArrayQueue;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn bounded_push_pop() {
+    let q = ArrayQueue::new(3);
+    assert_eq!(q.capacity(), 3);
+    assert!(q.is_empty());
+
+    assert_eq!(q.push(1), Ok(()));
+    assert_eq!(q.push(2), Ok(()));
+    assert_eq!(q.push(3), Ok(()));
+    assert!(q.is_full());
+    assert_eq!(q.len(), 3);
+    assert_eq!(q.push(4), Err(4));
+
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.push(4), Ok(()));
+    assert_eq!(q.pop(), Some(2));
+    assert_eq!(q.pop(), Some(3));
+    assert_eq!(q.pop(), Some(4));
+    assert_eq!(q.pop(), None);
+    assert_eq!(q.len(), 0);
+}
+
+#[test]
+fn force_push_overwrites_oldest() {
+    let q = ArrayQueue::new(2);
+    assert_eq!(q.force_push(1), None);
+    assert_eq!(q.force_push(2), None);
+    assert_eq!(q.force_push(3), Some(1));
+    assert_eq!(q.force_push(4), Some(2));
+    assert_eq!(q.len(), 2);
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), vec![3, 4]);
+}
+
+#[test]
+fn drop_remaining() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    struct Counted(Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let q = ArrayQueue::new(4);
+    for _ in 0..6 {
+        let _ = q.force_push(Counted(drops.clone()));
+    }
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+    drop(q);
+    assert_eq!(drops.load(Ordering::Relaxed), 6);
+}
+
+#[test]
+fn mpmc() {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let q = Arc::new(ArrayQueue::new(16));
+    let sum = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 1..=ITEMS {
+                    let mut v = i;
+                    while let Err(back) = q.push(v) {
+                        v = back;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let q = q.clone();
+            let sum = sum.clone();
+            thread::spawn(move || {
+                let mut n = 0;
+                while n < ITEMS {
+                    match q.pop() {
+                        Some(v) => {
+                            sum.fetch_add(v, Ordering::Relaxed);
+                            n += 1;
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for h in producers.into_iter().chain(consumers) {
+        h.join().unwrap();
+    }
+    assert!(q.is_empty());
+    assert_eq!(sum.load(Ordering::Relaxed), THREADS * ITEMS * (ITEMS + 1) / 2);
+}
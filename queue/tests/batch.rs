@@ -0,0 +1,57 @@
+use queue::AtomicQueue;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn batch_round_trip() {
+    let q = AtomicQueue::new();
+    q.enqueue_batch(Vec::<i32>::new());
+    assert!(q.is_empty());
+
+    q.enqueue(0);
+    q.enqueue_batch(1..10);
+    q.enqueue(10);
+
+    let mut buf = Vec::new();
+    assert_eq!(q.dequeue_batch(&mut buf, 0), 0);
+    assert_eq!(q.dequeue_batch(&mut buf, 4), 4);
+    assert_eq!(buf, vec![0, 1, 2, 3]);
+    assert_eq!(q.dequeue(), Some(4));
+    assert_eq!(q.dequeue_batch(&mut buf, 100), 6);
+    assert_eq!(buf, vec![0, 1, 2, 3, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(q.dequeue_batch(&mut buf, 100), 0);
+    assert!(q.is_empty());
+}
+
+#[test]
+fn concurrent_batches_keep_order() {
+    const BATCHES: usize = 500;
+    const SIZE: usize = 20;
+
+    let q = Arc::new(AtomicQueue::new());
+    let producers: Vec<_> = (0..2)
+        .map(|id| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for b in 0..BATCHES {
+                    q.enqueue_batch((0..SIZE).map(|i| (id, b * SIZE + i)));
+                }
+            })
+        })
+        .collect();
+
+    let mut next = [0; 2];
+    let mut buf = Vec::new();
+    while next.iter().sum::<usize>() < 2 * BATCHES * SIZE {
+        buf.clear();
+        q.dequeue_batch(&mut buf, 7);
+        for &(id, v) in &buf {
+            assert_eq!(next[id], v);
+            next[id] += 1;
+        }
+    }
+    for h in producers {
+        h.join().unwrap();
+    }
+    assert!(q.is_empty());
+}
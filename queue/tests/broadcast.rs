@@ -0,0 +1,101 @@
+use queue::channel::broadcast::{self, RecvError, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn every_receiver_sees_every_message() {
+    let (tx, mut a) = broadcast::channel(4);
+    let mut b = tx.subscribe();
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+    for i in 0..3 {
+        assert_eq!(a.recv(), Ok(i));
+        assert_eq!(b.try_recv(), Ok(i));
+    }
+    assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(b.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn late_subscriber_starts_at_tail() {
+    let (tx, mut a) = broadcast::channel(4);
+    tx.send(1).unwrap();
+    let mut b = tx.subscribe();
+    assert_eq!(b.len(), 0);
+    tx.send(2).unwrap();
+    assert_eq!(a.recv(), Ok(1));
+    assert_eq!(a.recv(), Ok(2));
+    assert_eq!(b.recv(), Ok(2));
+}
+
+#[test]
+fn slow_receiver_lags_instead_of_blocking() {
+    let (tx, mut slow) = broadcast::channel(3);
+    for i in 0..10 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(slow.len(), 10);
+    assert_eq!(slow.recv(), Err(RecvError::Lagged(7)));
+    for i in 7..10 {
+        assert_eq!(slow.recv(), Ok(i));
+    }
+    assert_eq!(slow.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn disconnect_after_drain() {
+    let (tx, mut rx) = broadcast::channel(2);
+    let tx2 = tx.clone();
+    tx.send(1).unwrap();
+    drop(tx);
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    drop(tx2);
+    assert_eq!(rx.recv(), Err(RecvError::Disconnected));
+
+    let (tx, rx) = broadcast::channel(2);
+    drop(rx);
+    assert_eq!(tx.receiver_count(), 0);
+    assert_eq!(tx.send(5).unwrap_err().into_inner(), 5);
+}
+
+#[test]
+fn recv_timeout_and_wakeup() {
+    let (tx, mut rx) = broadcast::channel::<u32>(2);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    const RECEIVERS: usize = 4;
+    const ITEMS: u32 = 1_000;
+    let handles: Vec<_> = (0..RECEIVERS)
+        .map(|_| {
+            let mut rx = tx.subscribe();
+            thread::spawn(move || {
+                let mut last = None;
+                let mut seen = 0;
+                loop {
+                    match rx.recv() {
+                        Ok(v) => {
+                            assert!(last.is_none_or(|l| v > l));
+                            last = Some(v);
+                            seen += 1;
+                        }
+                        Err(RecvError::Lagged(n)) => seen += n,
+                        Err(RecvError::Disconnected) => break,
+                    }
+                }
+                assert_eq!(seen, ITEMS as u64);
+                assert_eq!(last, Some(ITEMS - 1));
+            })
+        })
+        .collect();
+    for i in 0..ITEMS {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(rx.recv(), Err(RecvError::Lagged(ITEMS as u64 - 2)));
+}
@@ -0,0 +1,224 @@
+use queue::channel::{
+    self, RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn send_recv() {
+    let (tx, rx) = channel::unbounded();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(rx.try_recv(), Ok(2));
+}
+
+#[test]
+fn recv_blocks_until_send() {
+    let (tx, rx) = channel::unbounded();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send("hello").unwrap();
+    });
+    assert_eq!(rx.recv(), Ok("hello"));
+    h.join().unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let (tx, rx) = channel::unbounded::<i32>();
+    let start = Instant::now();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(50)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    tx.send(7).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_millis(50)), Ok(7));
+}
+
+#[test]
+fn disconnection() {
+    let (tx, rx) = channel::unbounded();
+    let tx2 = tx.clone();
+    tx.send(1).unwrap();
+    drop(tx);
+    assert!(!rx.is_disconnected());
+    drop(tx2);
+
+    //buffered messages are still delivered after disconnection
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(rx.recv(), Err(RecvError::Disconnected));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (tx, rx) = channel::unbounded();
+    drop(rx);
+    assert_eq!(tx.send(5), Err(SendError(5)));
+}
+
+#[test]
+fn blocked_receiver_wakes_on_disconnect() {
+    let (tx, rx) = channel::unbounded::<i32>();
+    let h = thread::spawn(move || rx.recv());
+    thread::sleep(Duration::from_millis(50));
+    drop(tx);
+    assert_eq!(h.join().unwrap(), Err(RecvError::Disconnected));
+}
+
+#[test]
+fn mpmc() {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let (tx, rx) = channel::unbounded();
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 1..=ITEMS {
+                    tx.send(i).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                let mut sum = 0;
+                while let Ok(v) = rx.recv() {
+                    sum += v;
+                }
+                sum
+            })
+        })
+        .collect();
+
+    for h in producers {
+        h.join().unwrap();
+    }
+    let sum: usize = consumers.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(sum, THREADS * ITEMS * (ITEMS + 1) / 2);
+}
+
+#[test]
+fn bounded_backpressure() {
+    let (tx, rx) = channel::bounded(2);
+    assert_eq!(tx.capacity(), Some(2));
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(
+        tx.send_timeout(3, Duration::from_millis(20)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+
+    //a blocked sender resumes as soon as a slot frees up
+    let h = thread::spawn(move || {
+        tx.send(3).unwrap();
+        tx
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.recv(), Ok(1));
+    let tx = h.join().unwrap();
+    assert_eq!(rx.recv(), Ok(2));
+    assert_eq!(rx.recv(), Ok(3));
+
+    drop(rx);
+    assert_eq!(tx.try_send(4), Err(TrySendError::Disconnected(4)));
+    assert_eq!(tx.send(4), Err(SendError(4)));
+}
+
+#[test]
+fn blocked_sender_wakes_on_disconnect() {
+    let (tx, rx) = channel::bounded(1);
+    tx.send(1).unwrap();
+    let h = thread::spawn(move || tx.send(2));
+    thread::sleep(Duration::from_millis(50));
+    drop(rx);
+    assert_eq!(h.join().unwrap(), Err(SendError(2)));
+}
+
+#[test]
+fn rendezvous() {
+    let (tx, rx) = channel::bounded(0);
+    assert_eq!(tx.capacity(), Some(0));
+
+    //nobody is receiving yet
+    assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+    assert_eq!(
+        tx.send_timeout(1, Duration::from_millis(20)),
+        Err(SendTimeoutError::Timeout(1))
+    );
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    let start = Instant::now();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        rx.recv().unwrap()
+    });
+    //send only returns once the receiver has taken the value
+    tx.send(5).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(h.join().unwrap(), 5);
+}
+
+#[test]
+fn rendezvous_try_send_to_blocked_receiver() {
+    let (tx, rx) = channel::bounded(0);
+    let h = thread::spawn(move || rx.recv());
+    let mut msg = 9;
+    loop {
+        match tx.try_send(msg) {
+            Ok(()) => break,
+            Err(TrySendError::Full(m)) => {
+                msg = m;
+                thread::sleep(Duration::from_millis(1));
+            }
+            Err(TrySendError::Disconnected(_)) => unreachable!(),
+        }
+    }
+    assert_eq!(h.join().unwrap(), Ok(9));
+}
+
+#[test]
+fn rendezvous_many() {
+    const ITEMS: usize = 1_000;
+
+    let (tx, rx) = channel::bounded(0);
+    let producers: Vec<_> = (0..2)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 1..=ITEMS {
+                    tx.send(i).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let consumers: Vec<_> = (0..2)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                let mut sum = 0;
+                while let Ok(v) = rx.recv() {
+                    sum += v;
+                }
+                sum
+            })
+        })
+        .collect();
+    drop(rx);
+
+    for h in producers {
+        h.join().unwrap();
+    }
+    let sum: usize = consumers.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(sum, 2 * ITEMS * (ITEMS + 1) / 2);
+}
@@ -0,0 +1,165 @@
+use core::future::Future;
+use core::task::{Context, Poll, Waker};
+use queue::channel::{self, RecvError, SendError};
+use queue::executor::block_on;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Wake;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn recv_async_waits_for_send() {
+    let (tx, rx) = channel::unbounded();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(1).unwrap();
+    });
+    assert_eq!(block_on(rx.recv_async()), Ok(1));
+    h.join().unwrap();
+    assert_eq!(block_on(rx.recv_async()), Err(RecvError::Disconnected));
+}
+
+#[test]
+fn send_async_backpressure() {
+    let (tx, rx) = channel::bounded(1);
+    block_on(tx.send_async(1)).unwrap();
+
+    let h = thread::spawn(move || {
+        block_on(async {
+            tx.send_async(2).await.unwrap();
+            tx.send_async(3).await
+        })
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(block_on(rx.recv_async()), Ok(2));
+    drop(rx);
+    //3 either landed in the buffer before the drop or bounced back
+    match h.join().unwrap() {
+        Ok(()) | Err(SendError(3)) => {}
+        Err(e) => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn rendezvous_async() {
+    let (tx, rx) = channel::bounded(0);
+    let h = thread::spawn(move || block_on(tx.send_async("ping")));
+    assert_eq!(block_on(rx.recv_async()), Ok("ping"));
+    assert_eq!(h.join().unwrap(), Ok(()));
+}
+
+#[test]
+fn dropped_future_leaves_messages_for_others() {
+    let (tx, rx) = channel::unbounded();
+    let mut fut = Box::pin(rx.recv_async());
+    //register a waker first in line
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+    let rx2 = rx.clone();
+    let h = thread::spawn(move || block_on(rx2.recv_async()));
+    thread::sleep(Duration::from_millis(20));
+    //the send wakes the abandoned future, dropping it must pass the wakeup on
+    tx.send(5).unwrap();
+    drop(fut);
+    assert_eq!(h.join().unwrap(), Ok(5));
+}
+
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn repolls_keep_one_registered_waker() {
+    let (tx, rx) = channel::unbounded();
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(rx.recv_async());
+
+    for _ in 0..100 {
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+    }
+    //the test's handle, `waker`, and the single copy kept in the waiter list
+    assert_eq!(Arc::strong_count(&counter), 3);
+
+    tx.send(1).unwrap();
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(1)));
+    drop(fut);
+    assert_eq!(Arc::strong_count(&counter), 2);
+}
+
+#[test]
+fn many_async_producers() {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 1_000;
+
+    let (tx, rx) = channel::bounded(4);
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    for i in 1..=ITEMS {
+                        tx.send_async(i).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let sum = block_on(async {
+        let mut sum = 0;
+        while let Ok(v) = rx.recv_async().await {
+            sum += v;
+        }
+        sum
+    });
+    for h in producers {
+        h.join().unwrap();
+    }
+    assert_eq!(sum, THREADS * ITEMS * (ITEMS + 1) / 2);
+}
+
+#[cfg(feature = "futures")]
+mod adapters {
+    use super::*;
+    use core::future::poll_fn;
+    use core::pin::Pin;
+    use futures_core::Stream;
+    use futures_sink::Sink;
+
+    #[test]
+    fn stream_and_sink() {
+        let (tx, rx) = channel::bounded(2);
+        let h = thread::spawn(move || {
+            let mut sink = tx.into_sink();
+            block_on(async {
+                for i in 0..10 {
+                    poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx)).await.unwrap();
+                    Pin::new(&mut sink).start_send(i).unwrap();
+                }
+                poll_fn(|cx| Pin::new(&mut sink).poll_close(cx)).await.unwrap();
+            })
+        });
+
+        let mut stream = rx.into_stream();
+        let got = block_on(async {
+            let mut got = Vec::new();
+            while let Some(v) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+                got.push(v);
+            }
+            got
+        });
+        h.join().unwrap();
+        assert_eq!(got, (0..10).collect::<Vec<_>>());
+    }
+}
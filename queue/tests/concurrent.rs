@@ -0,0 +1,120 @@
+use crossbeam::queue as cb;
+use queue::{ArrayQueue, AtomicQueue, ConcurrentQueue, Hazard, SegQueue, first};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+fn fifo<Q: ConcurrentQueue<usize>>(q: Q) {
+    assert!(q.is_empty());
+    assert_eq!(q.pop(), None);
+    for i in 0..100 {
+        q.push(i).unwrap();
+    }
+    assert!(!q.is_empty());
+    if let Some(len) = q.len() {
+        assert_eq!(len, 100);
+    }
+    for i in 0..100 {
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert!(q.is_empty());
+    assert_eq!(q.pop(), None);
+}
+
+fn mpmc<Q: ConcurrentQueue<usize> + Send + Sync + 'static>(q: Q) {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let q = Arc::new(q);
+    let sum = Arc::new(AtomicUsize::new(0));
+    let taken = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    let mut v = i;
+                    while let Err(back) = q.push(v) {
+                        v = back;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (q, sum, taken) = (q.clone(), sum.clone(), taken.clone());
+            thread::spawn(move || {
+                while taken.load(Ordering::Relaxed) < THREADS * ITEMS {
+                    match q.pop() {
+                        Some(v) => {
+                            sum.fetch_add(v, Ordering::Relaxed);
+                            taken.fetch_add(1, Ordering::Relaxed);
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|h| h.join().unwrap());
+    consumers.into_iter().for_each(|h| h.join().unwrap());
+    assert!(q.is_empty());
+    assert_eq!(
+        sum.load(Ordering::Relaxed),
+        THREADS * ITEMS * (ITEMS - 1) / 2
+    );
+}
+
+fn bounded<Q: ConcurrentQueue<usize>>(q: Q) {
+    let cap = q.capacity().unwrap();
+    for i in 0..cap {
+        q.push(i).unwrap();
+    }
+    assert_eq!(q.push(cap), Err(cap));
+    assert_eq!(q.pop(), Some(0));
+    q.push(cap).unwrap();
+    assert_eq!(q.len(), Some(cap));
+}
+
+#[test]
+fn fifo_all() {
+    fifo(AtomicQueue::new());
+    fifo(AtomicQueue::<_, Hazard>::with_reclaim());
+    fifo(first::AtomicQueue::new());
+    fifo(SegQueue::new());
+    fifo(ArrayQueue::new(128));
+    fifo(cb::SegQueue::new());
+    fifo(cb::ArrayQueue::new(128));
+}
+
+#[test]
+fn mpmc_all() {
+    mpmc(AtomicQueue::new());
+    mpmc(AtomicQueue::<_, Hazard>::with_reclaim());
+    mpmc(first::AtomicQueue::new());
+    mpmc(SegQueue::new());
+    mpmc(ArrayQueue::new(64));
+}
+
+#[test]
+fn bounded_all() {
+    bounded(ArrayQueue::new(8));
+    bounded(cb::ArrayQueue::new(8));
+}
+
+#[test]
+fn unbounded_have_no_capacity() {
+    assert_eq!(
+        ConcurrentQueue::<usize>::capacity(&AtomicQueue::new()),
+        None
+    );
+    assert_eq!(
+        ConcurrentQueue::<usize>::capacity(&first::AtomicQueue::new()),
+        None
+    );
+    assert_eq!(ConcurrentQueue::<usize>::capacity(&SegQueue::new()), None);
+}
@@ -0,0 +1,202 @@
+use queue::deque::{Steal, Worker};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn lifo_owner_fifo_stealer() {
+    let w = Worker::new_lifo();
+    let s = w.stealer();
+    for i in 0..5 {
+        w.push(i);
+    }
+    assert_eq!(w.len(), 5);
+    assert_eq!(w.pop(), Some(4));
+    assert_eq!(s.steal(), Steal::Success(0));
+    assert_eq!(w.pop(), Some(3));
+    assert_eq!(s.steal(), Steal::Success(1));
+    assert_eq!(w.pop(), Some(2));
+    assert_eq!(w.pop(), None);
+    assert_eq!(s.steal(), Steal::Empty);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn fifo_owner() {
+    let w = Worker::new_fifo();
+    for i in 0..5 {
+        w.push(i);
+    }
+    assert_eq!(
+        (0..5).map(|_| w.pop().unwrap()).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    assert_eq!(w.pop(), None);
+}
+
+#[test]
+fn grows_and_shrinks() {
+    let w = Worker::new_lifo();
+    let s = w.stealer();
+    for i in 0..10_000 {
+        w.push(i);
+    }
+    assert_eq!(s.len(), 10_000);
+    for i in (5_000..10_000).rev() {
+        assert_eq!(w.pop(), Some(i));
+    }
+    for i in 0..5_000 {
+        assert_eq!(s.steal(), Steal::Success(i));
+    }
+    assert!(w.is_empty());
+}
+
+#[test]
+fn steal_batch_moves_half() {
+    let src = Worker::new_fifo();
+    let dest = Worker::new_fifo();
+    for i in 0..10 {
+        src.push(i);
+    }
+    assert_eq!(src.stealer().steal_batch_into(&dest), Steal::Success(()));
+    assert_eq!(dest.len(), 5);
+    assert_eq!(src.len(), 5);
+    assert_eq!(
+        (0..5).map(|_| dest.pop().unwrap()).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    assert_eq!(src.pop(), Some(5));
+
+    let empty = Worker::<i32>::new_lifo();
+    assert_eq!(empty.stealer().steal_batch_into(&dest), Steal::Empty);
+}
+
+#[test]
+fn drops_remaining_tasks() {
+    struct Counted(Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let w = Worker::new_lifo();
+    let s = w.stealer();
+    for _ in 0..200 {
+        w.push(Counted(dropped.clone()));
+    }
+    drop(w.pop());
+    drop(s.steal());
+    drop(w);
+    assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    drop(s);
+    assert_eq!(dropped.load(Ordering::Relaxed), 200);
+}
+
+#[test]
+fn stealers_race_the_owner() {
+    const THIEVES: usize = 3;
+    const TASKS: usize = 100_000;
+
+    let w = Worker::new_lifo();
+    let total = Arc::new(AtomicUsize::new(0));
+    let seen = Arc::new(AtomicUsize::new(0));
+
+    let thieves: Vec<_> = (0..THIEVES)
+        .map(|i| {
+            let s = w.stealer();
+            let (total, seen) = (total.clone(), seen.clone());
+            thread::spawn(move || {
+                let local = Worker::new_fifo();
+                while seen.load(Ordering::Relaxed) < TASKS {
+                    //mix single steals and batches
+                    let got = if i % 2 == 0 {
+                        s.steal().success().map(|v| vec![v])
+                    } else {
+                        s.steal_batch_into(&local)
+                            .success()
+                            .map(|()| std::iter::from_fn(|| local.pop()).collect())
+                    };
+                    match got {
+                        Some(vs) => {
+                            for v in vs {
+                                total.fetch_add(v, Ordering::Relaxed);
+                                seen.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for i in 0..TASKS {
+        w.push(i);
+        if i % 3 == 0
+            && let Some(v) = w.pop()
+        {
+            total.fetch_add(v, Ordering::Relaxed);
+            seen.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    while let Some(v) = w.pop() {
+        total.fetch_add(v, Ordering::Relaxed);
+        seen.fetch_add(1, Ordering::Relaxed);
+    }
+    thieves.into_iter().for_each(|h| h.join().unwrap());
+
+    assert_eq!(seen.load(Ordering::Relaxed), TASKS);
+    assert_eq!(total.load(Ordering::Relaxed), TASKS * (TASKS - 1) / 2);
+}
+
+#[test]
+fn batch_steals_race_lifo_pops() {
+    const ROUNDS: usize = 100_000;
+
+    let w = Worker::<usize>::new_lifo();
+    let s = w.stealer();
+    let claimed: Arc<Vec<AtomicUsize>> =
+        Arc::new((0..ROUNDS * 8).map(|_| AtomicUsize::new(0)).collect());
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let thief = {
+        let (claimed, done) = (claimed.clone(), done.clone());
+        thread::spawn(move || {
+            let local = Worker::<usize>::new_lifo();
+            while done.load(Ordering::Relaxed) == 0 {
+                if s.steal_batch_into(&local).is_success() {
+                    while let Some(v) = local.pop() {
+                        claimed[v].fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        })
+    };
+
+    //the owner never pops its last task here, so only the thief moves `front`
+    //and a batch that read `back` early overlaps tasks already popped
+    let mut next = 0;
+    for round in 0..ROUNDS {
+        for _ in 0..round % 8 + 1 {
+            w.push(next);
+            next += 1;
+        }
+        while w.len() > 1 {
+            if let Some(v) = w.pop() {
+                claimed[v].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    done.store(1, Ordering::Relaxed);
+    thief.join().unwrap();
+    while let Some(v) = w.pop() {
+        claimed[v].fetch_add(1, Ordering::Relaxed);
+    }
+
+    //every task taken exactly once, by the owner or by the thief
+    for (v, c) in claimed[..next].iter().enumerate() {
+        assert_eq!(c.load(Ordering::Relaxed), 1, "task {}", v);
+    }
+}
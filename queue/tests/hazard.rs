@@ -0,0 +1,188 @@
+use queue::first::AtomicQueue;
+use queue::hazard::{self, HazardPointer};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, mpsc};
+use std::thread;
+
+//a stale hazard from a concurrent test could land on one of our addresses,
+//keep the exact-count checks from overlapping with the queue tests
+static SERIAL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+struct Counted(Arc<AtomicUsize>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn stalled_reader_only_delays_its_node() {
+    let _serial = SERIAL.lock().unwrap();
+    const NODES: usize = 1_000;
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let shared = Arc::new(AtomicPtr::new(ptr::null_mut::<Counted>()));
+    let nodes: Vec<*mut Counted> = (0..NODES)
+        .map(|_| Box::into_raw(Box::new(Counted(dropped.clone()))))
+        .collect();
+    let held = nodes[NODES / 2];
+    shared.store(held, Ordering::Release);
+
+    let (protected_tx, protected_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let reader = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            let mut hp = HazardPointer::new();
+            let p = hp.protect(&shared);
+            protected_tx.send(()).unwrap();
+            //stall while holding the hazard
+            release_rx.recv().unwrap();
+            assert!(!p.is_null());
+            drop(hp);
+        })
+    };
+    protected_rx.recv().unwrap();
+
+    shared.store(ptr::null_mut(), Ordering::Release);
+    for &n in &nodes {
+        unsafe { hazard::retire(n) };
+    }
+    hazard::flush();
+    assert_eq!(dropped.load(Ordering::Relaxed), NODES - 1);
+
+    release_tx.send(()).unwrap();
+    reader.join().unwrap();
+    hazard::flush();
+    assert_eq!(dropped.load(Ordering::Relaxed), NODES);
+}
+
+#[test]
+fn stalled_reader_does_not_block_scans() {
+    let _serial = SERIAL.lock().unwrap();
+    const ROUNDS: usize = 50;
+    const PER_ROUND: usize = 200;
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let shared = Arc::new(AtomicPtr::new(Box::into_raw(Box::new(Counted(
+        dropped.clone(),
+    )))));
+
+    let barrier = Arc::new(Barrier::new(2));
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let reader = {
+        let shared = shared.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            let mut hp = HazardPointer::new();
+            hp.protect(&shared);
+            barrier.wait();
+            release_rx.recv().unwrap();
+        })
+    };
+    barrier.wait();
+
+    //swap the published node out many times, the reader keeps its first one pinned
+    let mut retired = 0;
+    for _ in 0..ROUNDS {
+        for _ in 0..PER_ROUND {
+            let new = Box::into_raw(Box::new(Counted(dropped.clone())));
+            let old = shared.swap(new, Ordering::AcqRel);
+            unsafe { hazard::retire(old) };
+            retired += 1;
+        }
+        hazard::flush();
+        assert_eq!(dropped.load(Ordering::Relaxed), retired - 1);
+    }
+
+    release_tx.send(()).unwrap();
+    reader.join().unwrap();
+    hazard::flush();
+    assert_eq!(dropped.load(Ordering::Relaxed), retired);
+    drop(unsafe { Box::from_raw(shared.load(Ordering::Acquire)) });
+}
+
+#[test]
+fn first_queue_mpmc() {
+    let _serial = SERIAL.lock().unwrap();
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let q = Arc::new(AtomicQueue::new());
+    let taken = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|_| {
+            let q = q.clone();
+            let dropped = dropped.clone();
+            thread::spawn(move || {
+                for _ in 0..ITEMS {
+                    q.enqueue(Counted(dropped.clone()));
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..CONSUMERS)
+        .map(|_| {
+            let q = q.clone();
+            let taken = taken.clone();
+            thread::spawn(move || {
+                while taken.load(Ordering::Relaxed) < PRODUCERS * ITEMS {
+                    match q.dequeue() {
+                        Some(_) => {
+                            taken.fetch_add(1, Ordering::Relaxed);
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|h| h.join().unwrap());
+    consumers.into_iter().for_each(|h| h.join().unwrap());
+    assert!(q.is_empty());
+    assert_eq!(taken.load(Ordering::Relaxed), PRODUCERS * ITEMS);
+    assert_eq!(dropped.load(Ordering::Relaxed), PRODUCERS * ITEMS);
+}
+
+#[test]
+fn first_queue_drops_remaining_items() {
+    let _serial = SERIAL.lock().unwrap();
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let q = AtomicQueue::new();
+    for _ in 0..10 {
+        q.enqueue(Counted(dropped.clone()));
+    }
+    drop(q.dequeue());
+    drop(q);
+    assert_eq!(dropped.load(Ordering::Relaxed), 10);
+}
+
+//owns another retired node, so freeing it retires again from inside a scan
+struct Chained(*mut Counted);
+
+impl Drop for Chained {
+    fn drop(&mut self) {
+        unsafe { hazard::retire(self.0) };
+    }
+}
+
+#[test]
+fn deleters_may_retire_more_nodes() {
+    let _serial = SERIAL.lock().unwrap();
+    const NODES: usize = 500;
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    for _ in 0..NODES {
+        let inner = Box::into_raw(Box::new(Counted(dropped.clone())));
+        unsafe { hazard::retire(Box::into_raw(Box::new(Chained(inner)))) };
+    }
+    hazard::flush();
+    hazard::flush();
+    assert_eq!(dropped.load(Ordering::Relaxed), NODES);
+}
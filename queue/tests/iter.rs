@@ -0,0 +1,42 @@
+use queue::AtomicQueue;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn collect_and_extend() {
+    let mut q: AtomicQueue<i32> = (0..5).collect();
+    q.extend(5..8);
+    (&q).extend(vec![8, 9]);
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn try_iter_and_drain() {
+    let q: AtomicQueue<i32> = (0..10).collect();
+    assert_eq!(q.try_iter().take(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+    //dropping a partially consumed drain still empties the queue
+    let mut drain = q.drain();
+    assert_eq!(drain.next(), Some(3));
+    drop(drain);
+    assert!(q.is_empty());
+    assert_eq!(q.try_iter().count(), 0);
+}
+
+#[test]
+fn extend_from_many_threads() {
+    let q = Arc::new(AtomicQueue::new());
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let q = q.clone();
+            thread::spawn(move || (&*q).extend((0..1_000).map(|i| t * 1_000 + i)))
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    let mut all: Vec<_> = q.drain().collect();
+    all.sort();
+    assert_eq!(all, (0..4_000).collect::<Vec<_>>());
+}
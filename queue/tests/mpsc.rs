@@ -0,0 +1,185 @@
+use queue::mpsc::intrusive::{self, Link, Linked};
+use queue::mpsc::{self, TryPopError};
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn owned_fifo() {
+    let (tx, rx) = mpsc::queue();
+    assert!(rx.is_empty());
+    assert_eq!(rx.try_pop(), Err(TryPopError::Empty));
+    for i in 0..10 {
+        tx.push(i);
+    }
+    assert!(!rx.is_empty());
+    for i in 0..10 {
+        assert_eq!(rx.pop(), Some(i));
+    }
+    assert_eq!(rx.pop(), None);
+    assert!(rx.is_empty());
+
+    //the consumer can be handed to another thread, just not shared
+    assert_send::<mpsc::Consumer<i32>>();
+    assert_send::<mpsc::Producer<i32>>();
+}
+
+#[test]
+fn owned_many_producers() {
+    const PRODUCERS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let (tx, rx) = mpsc::queue();
+    let handles: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    tx.push((p, i));
+                }
+            })
+        })
+        .collect();
+
+    //per-producer order is preserved
+    let mut next = [0; PRODUCERS];
+    let mut got = 0;
+    while got < PRODUCERS * ITEMS {
+        match rx.pop() {
+            Some((p, i)) => {
+                assert_eq!(next[p], i);
+                next[p] += 1;
+                got += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(rx.pop(), None);
+}
+
+#[test]
+fn owned_drops_remaining_values() {
+    let value = Arc::new(());
+    let (tx, rx) = mpsc::queue();
+    for _ in 0..10 {
+        tx.push(value.clone());
+    }
+    drop(rx.pop());
+    drop(tx);
+    drop(rx);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+struct Job {
+    id: usize,
+    link: Link,
+}
+
+unsafe impl Linked for Job {
+    unsafe fn link(this: *const Self) -> *const Link {
+        unsafe { ptr::addr_of!((*this).link) }
+    }
+
+    unsafe fn from_link(link: *const Link) -> *const Self {
+        unsafe { link.byte_sub(mem::offset_of!(Job, link)).cast() }
+    }
+}
+
+fn job(id: usize) -> Box<Job> {
+    Box::new(Job {
+        id,
+        link: Link::new(),
+    })
+}
+
+#[test]
+fn intrusive_reuses_nodes() {
+    let (tx, rx) = intrusive::queue::<Job>();
+    tx.push(job(1));
+    tx.push(job(2));
+
+    let mut j = rx.pop().unwrap();
+    assert_eq!(j.id, 1);
+    //the same allocation goes around again
+    let addr = &*j as *const Job;
+    j.id = 3;
+    tx.push(j);
+
+    assert_eq!(rx.pop().unwrap().id, 2);
+    let j = rx.pop().unwrap();
+    assert_eq!((j.id, &*j as *const Job), (3, addr));
+    assert!(rx.pop().is_none());
+}
+
+#[test]
+fn intrusive_many_producers() {
+    const PRODUCERS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let (tx, rx) = intrusive::queue::<Job>();
+    let handles: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    tx.push(job(p * ITEMS + i));
+                }
+            })
+        })
+        .collect();
+
+    let consumer = thread::spawn(move || {
+        let mut sum = 0;
+        let mut got = 0;
+        while got < PRODUCERS * ITEMS {
+            match rx.try_pop() {
+                Ok(j) => {
+                    sum += j.id;
+                    got += 1;
+                }
+                Err(_) => thread::yield_now(),
+            }
+        }
+        sum
+    });
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    let n = PRODUCERS * ITEMS;
+    assert_eq!(consumer.join().unwrap(), n * (n - 1) / 2);
+}
+
+#[test]
+fn intrusive_drops_remaining_nodes() {
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Tracked {
+        link: Link,
+    }
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    unsafe impl Linked for Tracked {
+        unsafe fn link(this: *const Self) -> *const Link {
+            unsafe { ptr::addr_of!((*this).link) }
+        }
+
+        unsafe fn from_link(link: *const Link) -> *const Self {
+            unsafe { link.byte_sub(mem::offset_of!(Tracked, link)).cast() }
+        }
+    }
+
+    let (tx, rx) = intrusive::queue();
+    for _ in 0..5 {
+        tx.push(Box::new(Tracked { link: Link::new() }));
+    }
+    drop(rx.pop());
+    drop(rx);
+    drop(tx);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 5);
+}
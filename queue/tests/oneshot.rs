@@ -0,0 +1,71 @@
+use queue::channel::oneshot;
+use queue::channel::{RecvError, RecvTimeoutError, TryRecvError};
+use queue::executor::block_on;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn send_then_recv() {
+    let (tx, mut rx) = oneshot::channel();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert!(!tx.is_canceled());
+    tx.send(7).unwrap();
+    assert_eq!(rx.try_recv(), Ok(7));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn blocking_recv_waits() {
+    let (tx, rx) = oneshot::channel();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send("reply").unwrap();
+    });
+    assert_eq!(rx.recv(), Ok("reply"));
+    h.join().unwrap();
+}
+
+#[test]
+fn cancellation_both_ways() {
+    let (tx, mut rx) = oneshot::channel::<u32>();
+    assert!(!rx.is_canceled());
+    drop(tx);
+    assert!(rx.is_canceled());
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(rx.recv(), Err(RecvError::Disconnected));
+
+    let (tx, rx) = oneshot::channel();
+    drop(rx);
+    assert!(tx.is_canceled());
+    assert_eq!(tx.send(1).unwrap_err().into_inner(), 1);
+}
+
+#[test]
+fn recv_timeout_expires() {
+    let (tx, mut rx) = oneshot::channel::<u32>();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    tx.send(3).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(3));
+}
+
+#[test]
+fn receiver_is_a_future() {
+    let (tx, rx) = oneshot::channel();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(5).unwrap();
+    });
+    assert_eq!(block_on(rx), Ok(5));
+    h.join().unwrap();
+
+    let (tx, rx) = oneshot::channel::<u32>();
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        drop(tx);
+    });
+    assert_eq!(block_on(rx), Err(RecvError::Disconnected));
+    h.join().unwrap();
+}
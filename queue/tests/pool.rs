@@ -0,0 +1,130 @@
+use queue::ThreadPool;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn spawn_runs_every_job_before_shutdown() {
+    let pool = ThreadPool::new(4);
+    let count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..1_000 {
+        let count = count.clone();
+        pool.spawn(move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    pool.shutdown();
+    assert_eq!(count.load(Ordering::Relaxed), 1_000);
+}
+
+#[test]
+fn jobs_spawned_from_jobs_still_run() {
+    let pool = ThreadPool::new(2);
+    let count = AtomicUsize::new(0);
+    pool.scope(|s| {
+        for _ in 0..10 {
+            s.spawn(|s| {
+                for _ in 0..10 {
+                    s.spawn(|_| {
+                        count.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            });
+        }
+    });
+    assert_eq!(count.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn scope_borrows_from_the_stack() {
+    let pool = ThreadPool::new(3);
+    let mut data = vec![1u64; 1_000];
+    pool.scope(|s| {
+        for chunk in data.chunks_mut(100) {
+            s.spawn(move |_| chunk.iter_mut().for_each(|x| *x *= 2));
+        }
+    });
+    assert!(data.iter().all(|&x| x == 2));
+}
+
+fn sum(pool: &ThreadPool, v: &[u64]) -> u64 {
+    if v.len() <= 64 {
+        return v.iter().sum();
+    }
+    let (l, r) = v.split_at(v.len() / 2);
+    let (a, b) = pool.join(|| sum(pool, l), || sum(pool, r));
+    a + b
+}
+
+#[test]
+fn recursive_join() {
+    const EXPECTED: u64 = 100_000 * 99_999 / 2;
+
+    let pool = ThreadPool::new(4);
+    let v: Vec<u64> = (0..100_000).collect();
+    assert_eq!(sum(&pool, &v), EXPECTED);
+
+    //joins started on a worker help out instead of blocking it
+    let mut total = 0;
+    pool.scope(|s| s.spawn(|_| total = sum(&pool, &v)));
+    assert_eq!(total, EXPECTED);
+}
+
+#[test]
+fn scope_propagates_job_panic() {
+    let pool = ThreadPool::new(2);
+    let done = AtomicUsize::new(0);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.spawn(|_| panic!("boom"));
+            s.spawn(|_| {
+                thread::sleep(Duration::from_millis(20));
+                done.fetch_add(1, Ordering::Relaxed);
+            });
+        })
+    }));
+    let err = res.unwrap_err();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"boom"));
+    //the scope still waited for the other job
+    assert_eq!(done.load(Ordering::Relaxed), 1);
+
+    //the pool is still usable afterwards
+    assert_eq!(pool.join(|| 1, || 2), (1, 2));
+}
+
+#[test]
+fn join_propagates_panic() {
+    let pool = ThreadPool::new(2);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.join(|| 1, || -> i32 { panic!("right") })
+    }));
+    assert!(res.is_err());
+}
+
+#[test]
+fn shutdown_reraises_spawn_panic() {
+    let pool = ThreadPool::new(2);
+    let count = Arc::new(AtomicUsize::new(0));
+    pool.spawn(|| panic!("spawned"));
+    for _ in 0..10 {
+        let count = count.clone();
+        pool.spawn(move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    let res = panic::catch_unwind(AssertUnwindSafe(|| pool.shutdown()));
+    assert_eq!(res.unwrap_err().downcast_ref::<&str>(), Some(&"spawned"));
+    assert_eq!(count.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn idle_workers_wake_up() {
+    let pool = ThreadPool::new(2);
+    //let the workers fall asleep first
+    thread::sleep(Duration::from_millis(50));
+    let (tx, rx) = std::sync::mpsc::channel();
+    pool.spawn(move || tx.send(7).unwrap());
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(7));
+}
@@ -0,0 +1,150 @@
+use crossbeam::epoch;
+use queue::PriorityQueue;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[test]
+fn pops_in_priority_order() {
+    let q = PriorityQueue::new();
+    for p in [5, 1, 4, 2, 3] {
+        q.push(p, p * 10);
+    }
+    assert_eq!(q.len(), 5);
+    for p in 1..=5 {
+        assert_eq!(q.pop_min(), Some((p, p * 10)));
+    }
+    assert!(q.is_empty());
+    assert_eq!(q.pop_min(), None);
+}
+
+#[test]
+fn equal_priorities_are_fifo() {
+    let q = PriorityQueue::new();
+    for v in 0..10 {
+        q.push(1, v);
+    }
+    q.push(0, 100);
+    assert_eq!(q.pop_min(), Some((0, 100)));
+    for v in 0..10 {
+        assert_eq!(q.pop_min(), Some((1, v)));
+    }
+}
+
+#[test]
+fn peek_min_under_guard() {
+    let q = PriorityQueue::new();
+    let guard = &epoch::pin();
+    assert!(q.peek_min(guard).is_none());
+    q.push(2, "two");
+    q.push(1, "one");
+    {
+        let e = q.peek_min(guard).unwrap();
+        assert_eq!((*e.priority(), *e.value()), (1, "one"));
+    }
+    assert_eq!(q.pop_min(), Some((1, "one")));
+    assert_eq!(*q.peek_min(guard).unwrap().value(), "two");
+}
+
+#[test]
+fn approx_min_stays_near_the_front() {
+    let q = PriorityQueue::new();
+    for p in 0..1_000 {
+        q.push(p, ());
+    }
+    let mut seen = HashSet::new();
+    for _ in 0..100 {
+        let (p, ()) = q.pop_approx_min(4).unwrap();
+        assert!(seen.insert(p));
+    }
+    //100 pops with a small spread never dig deep into the queue
+    assert!(seen.iter().all(|&p| p < 400), "{:?}", seen);
+    assert_eq!(q.len(), 900);
+}
+
+#[test]
+fn drops_remaining_values() {
+    let value = Arc::new(());
+    let q = PriorityQueue::new();
+    for p in 0..100 {
+        q.push(p, value.clone());
+    }
+    drop(q.pop_min());
+    drop(q.pop_approx_min(8));
+    drop(q);
+    //popped nodes keep their copy until the epoch frees them
+    for _ in 0..1_000 {
+        if Arc::strong_count(&value) == 1 {
+            break;
+        }
+        epoch::pin().flush();
+        thread::yield_now();
+    }
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn pop_while_entry_is_alive() {
+    let q = PriorityQueue::new();
+    q.push(1, String::from("one"));
+    q.push(2, String::from("two"));
+    let guard = &epoch::pin();
+    let e = q.peek_min(guard).unwrap();
+    //neither waits for the other, the entry still reads the popped element
+    assert_eq!(q.pop_min(), Some((1, String::from("one"))));
+    assert_eq!((*e.priority(), e.value().as_str()), (1, "one"));
+    assert_eq!(*q.peek_min(guard).unwrap().priority(), 2);
+    assert_eq!(q.len(), 1);
+}
+
+fn concurrent(approx: bool) {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 5_000;
+
+    let q = Arc::new(PriorityQueue::new());
+    let popped = Arc::new(Mutex::new(Vec::new()));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let (q, popped) = (q.clone(), popped.clone());
+            thread::spawn(move || {
+                let mut mine = Vec::new();
+                for i in 0..ITEMS {
+                    let p = (i * THREADS + t) % 997;
+                    q.push(p, i * THREADS + t);
+                    if i % 2 == 1 {
+                        let got = if approx {
+                            q.pop_approx_min(THREADS)
+                        } else {
+                            q.pop_min()
+                        };
+                        mine.push(got.unwrap().1);
+                    }
+                }
+                popped.lock().unwrap().extend(mine);
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+
+    let mut all = popped.lock().unwrap().clone();
+    let mut last = None;
+    while let Some((p, v)) = q.pop_min() {
+        //single-threaded now, so strictly ordered again
+        assert!(last <= Some(p));
+        last = Some(p);
+        all.push(v);
+    }
+    all.sort_unstable();
+    assert_eq!(all, (0..THREADS * ITEMS).collect::<Vec<_>>());
+    assert!(q.is_empty());
+}
+
+#[test]
+fn concurrent_pop_min() {
+    concurrent(false);
+}
+
+#[test]
+fn concurrent_pop_approx_min() {
+    concurrent(true);
+}
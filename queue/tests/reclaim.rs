@@ -0,0 +1,97 @@
+use queue::{AtomicQueue, Epoch, Hazard, Reclaim};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+//counts live heap allocations made by the whole test binary
+struct Tracking;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            LIVE.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Tracking = Tracking;
+
+//both checks share one allocation counter, keep them from overlapping
+static SERIAL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn epoch_frees_dequeued_nodes_in_steady_state() {
+    steady_state::<Epoch>();
+}
+
+#[test]
+fn hazard_frees_dequeued_nodes_in_steady_state() {
+    steady_state::<Hazard>();
+}
+
+fn steady_state<R: Reclaim + 'static>() {
+    const ROUNDS: usize = 20;
+    const PER_ROUND: usize = 10_000;
+
+    let _serial = SERIAL.lock().unwrap();
+    let q = Arc::new(AtomicQueue::<usize, R>::with_reclaim());
+    let done = Arc::new(AtomicBool::new(false));
+
+    //long-lived consumer that keeps running between rounds, like a service worker
+    let consumer = {
+        let q = q.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            let mut n = 0;
+            while !done.load(Ordering::Acquire) || !q.is_empty() {
+                if q.dequeue().is_some() {
+                    n += 1;
+                } else {
+                    thread::yield_now();
+                }
+            }
+            n
+        })
+    };
+
+    let mut samples = Vec::new();
+    for _ in 0..ROUNDS {
+        for i in 0..PER_ROUND {
+            q.enqueue(i);
+        }
+        while !q.is_empty() {
+            thread::yield_now();
+        }
+        samples.push(LIVE.load(Ordering::Relaxed));
+    }
+    done.store(true, Ordering::Release);
+    assert_eq!(consumer.join().unwrap(), ROUNDS * PER_ROUND);
+
+    //without reclamation every round would leave PER_ROUND more nodes alive
+    let first = samples[ROUNDS / 2];
+    let last = samples[ROUNDS - 1];
+    assert!(
+        last < first + PER_ROUND,
+        "live allocations grew from {} to {}: {:?}",
+        first,
+        last,
+        samples
+    );
+    assert!(
+        last < ROUNDS * PER_ROUND / 4,
+        "{} live allocations after {} operations",
+        last,
+        ROUNDS * PER_ROUND
+    );
+}
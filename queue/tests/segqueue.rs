@@ -0,0 +1,122 @@
+use queue::SegQueue;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn fifo_single_thread() {
+    let q = SegQueue::new();
+    assert!(q.is_empty());
+    assert_eq!(q.pop(), None);
+
+    //cross several block boundaries
+    for i in 0..100 {
+        q.push(i);
+    }
+    assert_eq!(q.len(), 100);
+    for i in 0..100 {
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert!(q.is_empty());
+    assert_eq!(q.len(), 0);
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn into_iter_and_drop_remaining() {
+    struct Counted(Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let q = SegQueue::new();
+    for _ in 0..70 {
+        q.push(Counted(drops.clone()));
+    }
+    drop(q.pop());
+    drop(q);
+    assert_eq!(drops.load(Ordering::Relaxed), 70);
+
+    let q = SegQueue::new();
+    for i in 0..70 {
+        q.push(i);
+    }
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), (0..70).collect::<Vec<_>>());
+}
+
+#[test]
+fn mpmc() {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let q = Arc::new(SegQueue::new());
+    let sum = Arc::new(AtomicUsize::new(0));
+    let popped = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 1..=ITEMS {
+                    q.push(i);
+                }
+            })
+        })
+        .collect();
+
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let q = q.clone();
+            let sum = sum.clone();
+            let popped = popped.clone();
+            thread::spawn(move || {
+                while popped.load(Ordering::Relaxed) < THREADS * ITEMS {
+                    if let Some(v) = q.pop() {
+                        sum.fetch_add(v, Ordering::Relaxed);
+                        popped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for h in producers.into_iter().chain(consumers) {
+        h.join().unwrap();
+    }
+
+    assert!(q.is_empty());
+    assert_eq!(sum.load(Ordering::Relaxed), THREADS * ITEMS * (ITEMS + 1) / 2);
+}
+
+#[test]
+fn per_producer_order() {
+    const ITEMS: usize = 10_000;
+
+    let q = Arc::new(SegQueue::new());
+    let producers: Vec<_> = (0..2)
+        .map(|id| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    q.push((id, i));
+                }
+            })
+        })
+        .collect();
+
+    let mut next = [0; 2];
+    let mut seen = 0;
+    while seen < 2 * ITEMS {
+        if let Some((id, i)) = q.pop() {
+            assert_eq!(next[id], i);
+            next[id] += 1;
+            seen += 1;
+        }
+    }
+    for h in producers {
+        h.join().unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use queue::channel::{self, RecvError, Select, SelectTimeoutError, TrySelectError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn try_select_picks_ready_receiver() {
+    let (_tx1, rx1) = channel::unbounded::<i32>();
+    let (tx2, rx2) = channel::unbounded();
+
+    let mut sel = Select::new();
+    let i1 = sel.recv(&rx1);
+    let i2 = sel.recv(&rx2);
+    assert_eq!(sel.try_select().unwrap_err(), TrySelectError);
+
+    tx2.send("data").unwrap();
+    let op = sel.try_select().unwrap();
+    assert_eq!(op.index(), i2);
+    assert_ne!(op.index(), i1);
+    assert_eq!(op.recv(&rx2), Ok("data"));
+}
+
+#[test]
+fn select_blocks_until_ready() {
+    let (_ctl_tx, ctl) = channel::unbounded::<()>();
+    let (data_tx, data) = channel::unbounded();
+
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        data_tx.send(42).unwrap();
+    });
+
+    let mut sel = Select::new();
+    sel.recv(&ctl);
+    let d = sel.recv(&data);
+    let op = sel.select();
+    assert_eq!(op.index(), d);
+    assert_eq!(op.recv(&data), Ok(42));
+    h.join().unwrap();
+}
+
+#[test]
+fn select_timeout() {
+    let (_tx, rx) = channel::unbounded::<i32>();
+    let mut sel = Select::new();
+    sel.recv(&rx);
+
+    let start = Instant::now();
+    assert_eq!(
+        sel.select_timeout(Duration::from_millis(50)).unwrap_err(),
+        SelectTimeoutError
+    );
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn disconnected_receiver_is_ready() {
+    let (tx, rx) = channel::unbounded::<i32>();
+    let (_shutdown_tx, shutdown) = channel::unbounded::<()>();
+    drop(tx);
+
+    let mut sel = Select::new();
+    let i = sel.recv(&rx);
+    sel.recv(&shutdown);
+    let op = sel.select();
+    assert_eq!(op.index(), i);
+    assert_eq!(op.recv(&rx), Err(RecvError::Disconnected));
+}
+
+#[test]
+fn select_send_on_bounded() {
+    let (tx, rx) = channel::bounded(1);
+    tx.send(1).unwrap();
+
+    let mut sel = Select::new();
+    let s = sel.send(&tx);
+    assert!(sel.try_select().is_err());
+
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(rx.recv(), Ok(1));
+        rx
+    });
+    let op = sel.select();
+    assert_eq!(op.index(), s);
+    op.send(&tx, 2).unwrap();
+    assert_eq!(h.join().unwrap().recv(), Ok(2));
+}
+
+#[test]
+fn select_send_on_rendezvous() {
+    let (tx, rx) = channel::bounded(0);
+    let mut sel = Select::new();
+    let s = sel.send(&tx);
+    assert!(sel.try_select().is_err());
+
+    let h = thread::spawn(move || rx.recv().unwrap());
+    let op = sel.select();
+    assert_eq!(op.index(), s);
+    op.send(&tx, 7).unwrap();
+    assert_eq!(h.join().unwrap(), 7);
+}
+
+#[test]
+fn fair_between_busy_receivers() {
+    let (tx1, rx1) = channel::unbounded();
+    let (tx2, rx2) = channel::unbounded();
+    for i in 0..1_000 {
+        tx1.send(i).unwrap();
+        tx2.send(i).unwrap();
+    }
+
+    let mut hits = [0; 2];
+    for _ in 0..1_000 {
+        let mut sel = Select::new();
+        let a = sel.recv(&rx1);
+        sel.recv(&rx2);
+        let op = sel.select();
+        if op.index() == a {
+            op.recv(&rx1).unwrap();
+            hits[0] += 1;
+        } else {
+            op.recv(&rx2).unwrap();
+            hits[1] += 1;
+        }
+    }
+    assert!(hits[0] > 300 && hits[1] > 300, "unfair split {:?}", hits);
+}
+
+#[test]
+fn many_producers_one_selector() {
+    const ITEMS: usize = 1_000;
+
+    let (tx1, rx1) = channel::bounded(4);
+    let (tx2, rx2) = channel::unbounded();
+    let h1 = thread::spawn(move || (0..ITEMS).for_each(|i| tx1.send(i).unwrap()));
+    let h2 = thread::spawn(move || (0..ITEMS).for_each(|i| tx2.send(i).unwrap()));
+
+    let mut open = 2;
+    let mut sum = 0;
+    let mut sel = Select::new();
+    let a = sel.recv(&rx1);
+    let b = sel.recv(&rx2);
+    while open > 0 {
+        let op = sel.select();
+        let i = op.index();
+        let res = if i == a { op.recv(&rx1) } else { op.recv(&rx2) };
+        match res {
+            Ok(v) => sum += v,
+            Err(RecvError::Disconnected) => {
+                sel.remove(if i == a { a } else { b });
+                open -= 1;
+            }
+        }
+    }
+    h1.join().unwrap();
+    h2.join().unwrap();
+    assert_eq!(sum, 2 * ITEMS * (ITEMS - 1) / 2);
+}
@@ -0,0 +1,95 @@
+use queue::spsc;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn push_pop_until_full() {
+    let (mut tx, mut rx) = spsc::ring(3);
+    assert_eq!(tx.capacity(), 3);
+    assert!(rx.is_empty());
+    for i in 0..3 {
+        tx.push(i).unwrap();
+    }
+    assert!(tx.is_full());
+    assert_eq!(tx.push(3), Err(3));
+    assert_eq!(rx.len(), 3);
+    assert_eq!(rx.peek(), Some(&0));
+    assert_eq!(rx.pop(), Some(0));
+    tx.push(3).unwrap();
+    for i in 1..4 {
+        assert_eq!(rx.pop(), Some(i));
+    }
+    assert_eq!(rx.pop(), None);
+}
+
+#[test]
+fn chunks_wrap_around() {
+    let (mut tx, mut rx) = spsc::ring(8);
+    let mut buf = [0u32; 8];
+
+    assert_eq!(tx.write_chunk(&[1, 2, 3, 4, 5, 6]), 6);
+    assert_eq!(rx.read_chunk(&mut buf[..4]), 4);
+    assert_eq!(&buf[..4], &[1, 2, 3, 4]);
+
+    //crosses the end of the buffer and gets cut at the capacity
+    assert_eq!(tx.write_chunk(&[7, 8, 9, 10, 11, 12, 13]), 6);
+    assert_eq!(tx.write_chunk(&[14]), 0);
+    assert_eq!(rx.read_chunk(&mut buf), 8);
+    assert_eq!(buf, [5, 6, 7, 8, 9, 10, 11, 12]);
+    assert_eq!(rx.read_chunk(&mut buf), 0);
+}
+
+#[test]
+fn drops_unread_items() {
+    let value = Arc::new(());
+    let (mut tx, mut rx) = spsc::ring(4);
+    for _ in 0..4 {
+        tx.push(value.clone()).unwrap();
+    }
+    drop(rx.pop());
+    drop(tx);
+    drop(rx);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn pipeline_between_threads() {
+    const ITEMS: u64 = 100_000;
+
+    let (mut tx, mut rx) = spsc::ring(64);
+    let producer = thread::spawn(move || {
+        let mut next = 0;
+        while next < ITEMS {
+            //mix single pushes and chunks
+            let before = next;
+            if next % 3 == 0 {
+                let chunk: Vec<u64> = (next..(next + 10).min(ITEMS)).collect();
+                next += tx.write_chunk(&chunk) as u64;
+            } else if tx.push(next).is_ok() {
+                next += 1;
+            }
+            if next == before {
+                thread::yield_now();
+            }
+        }
+    });
+
+    let mut expected = 0;
+    let mut buf = [0u64; 16];
+    while expected < ITEMS {
+        let n = rx.read_chunk(&mut buf);
+        for &v in &buf[..n] {
+            assert_eq!(v, expected);
+            expected += 1;
+        }
+        if let Some(v) = rx.pop() {
+            assert_eq!(v, expected);
+            expected += 1;
+        }
+        if n == 0 && rx.is_empty() {
+            thread::yield_now();
+        }
+    }
+    producer.join().unwrap();
+    assert!(rx.is_empty());
+}
@@ -0,0 +1,119 @@
+use queue::Stack;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn lifo() {
+    let s = Stack::new();
+    assert!(s.is_empty());
+    assert_eq!(s.pop(), None);
+    for i in 0..10 {
+        s.push(i);
+    }
+    assert_eq!(s.peek_with(|v| *v), Some(9));
+    for i in (0..10).rev() {
+        assert_eq!(s.pop(), Some(i));
+    }
+    assert!(s.is_empty());
+    assert_eq!(s.peek_with(|v| *v), None);
+}
+
+#[test]
+fn into_iter_is_top_first() {
+    let s: Stack<_> = (0..5).collect();
+    assert_eq!(s.into_iter().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn drops_remaining_items() {
+    struct Counted(Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let s = Stack::new();
+    for _ in 0..10 {
+        s.push(Counted(dropped.clone()));
+    }
+    drop(s.pop());
+    drop(s);
+    assert_eq!(dropped.load(Ordering::Relaxed), 10);
+}
+
+fn mpmc(s: Stack<usize>) {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 10_000;
+
+    let s = Arc::new(s);
+    let sum = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (s, sum) = (s.clone(), sum.clone());
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    s.push(i);
+                    if let Some(v) = s.pop() {
+                        sum.fetch_add(v, Ordering::Relaxed);
+                    }
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+
+    let rest: usize = Arc::try_unwrap(s).unwrap().into_iter().sum();
+    assert_eq!(
+        sum.load(Ordering::Relaxed) + rest,
+        THREADS * ITEMS * (ITEMS - 1) / 2
+    );
+}
+
+#[test]
+fn mpmc_plain() {
+    mpmc(Stack::new());
+}
+
+#[test]
+fn mpmc_with_elimination() {
+    mpmc(Stack::with_elimination(2));
+}
+
+#[test]
+fn peek_while_popping() {
+    const ITEMS: usize = 10_000;
+
+    let s = Arc::new(Stack::new());
+    for i in 0..ITEMS {
+        s.push([i; 4]);
+    }
+    let peeker = {
+        let s = s.clone();
+        thread::spawn(move || {
+            //every element seen must still be intact
+            while let Some(v) = s.peek_with(|v| *v) {
+                assert!(v.iter().all(|&x| x == v[0]));
+            }
+        })
+    };
+    while s.pop().is_some() {}
+    peeker.join().unwrap();
+}
+
+#[test]
+fn pop_inside_peek() {
+    let s = Stack::new();
+    s.push(1);
+    s.push(2);
+    //the peeked element is popped from inside the closure, neither side waits
+    let seen = s.peek_with(|v| {
+        assert_eq!(s.pop(), Some(2));
+        *v
+    });
+    assert_eq!(seen, Some(2));
+    assert_eq!(s.pop(), Some(1));
+    assert_eq!(s.peek_with(|v| *v), None);
+}
@@ -0,0 +1,97 @@
+use queue::channel::watch;
+use queue::channel::{RecvError, RecvTimeoutError};
+use queue::executor::block_on;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn borrow_sees_latest_value() {
+    let (tx, mut rx) = watch::channel(1);
+    assert_eq!(*rx.borrow(), 1);
+    assert_eq!(rx.has_changed(), Ok(false));
+    tx.send(2).unwrap();
+    tx.send(3).unwrap();
+    assert_eq!(rx.has_changed(), Ok(true));
+    assert_eq!(*rx.borrow_and_update(), 3);
+    assert_eq!(rx.has_changed(), Ok(false));
+    assert_eq!(tx.send_replace(4), 3);
+    tx.send_modify(|v| *v += 1);
+    assert_eq!(*tx.borrow(), 5);
+}
+
+#[test]
+fn changed_blocks_until_newer_version() {
+    let (tx, mut rx) = watch::channel(0);
+    assert_eq!(
+        rx.changed_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    let h = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(1).unwrap();
+        tx
+    });
+    assert_eq!(rx.changed(), Ok(()));
+    assert_eq!(*rx.borrow(), 1);
+    drop(h.join().unwrap());
+    assert_eq!(rx.changed(), Err(RecvError::Disconnected));
+}
+
+#[test]
+fn pending_change_survives_sender_drop() {
+    let (tx, mut rx) = watch::channel("a");
+    tx.send("b").unwrap();
+    drop(tx);
+    assert_eq!(rx.has_changed(), Ok(true));
+    assert_eq!(rx.changed(), Ok(()));
+    assert_eq!(*rx.borrow(), "b");
+    assert_eq!(rx.has_changed(), Err(RecvError::Disconnected));
+}
+
+#[test]
+fn subscribers_and_receiver_count() {
+    let (tx, rx) = watch::channel(0);
+    tx.send(1).unwrap();
+    let mut late = tx.subscribe();
+    assert_eq!(late.has_changed(), Ok(false));
+    let copy = rx.clone();
+    assert_eq!(copy.has_changed(), Ok(true));
+    assert_eq!(tx.receiver_count(), 3);
+    drop((rx, copy));
+    tx.send(2).unwrap();
+    assert_eq!(late.changed(), Ok(()));
+    drop(late);
+    assert_eq!(tx.send(3).unwrap_err().into_inner(), 3);
+    //still stored for future subscribers
+    tx.send_replace(4);
+    assert_eq!(*tx.subscribe().borrow(), 4);
+}
+
+#[test]
+fn changed_async_wakes_every_receiver() {
+    const RECEIVERS: usize = 4;
+    let (tx, rx) = watch::channel(0u32);
+    let handles: Vec<_> = (0..RECEIVERS)
+        .map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    let mut last = 0;
+                    while rx.changed_async().await.is_ok() {
+                        let v = *rx.borrow_and_update();
+                        assert!(v >= last);
+                        last = v;
+                    }
+                    last
+                })
+            })
+        })
+        .collect();
+    for i in 1..=100 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    for h in handles {
+        assert_eq!(h.join().unwrap(), 100);
+    }
+}
@@ -0,0 +1,105 @@
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, Criterion, criterion_group, criterion_main};
+use spinlock::first;
+use spinlock::second::{self, ClhLock};
+use spinlock::ticket::TicketLock;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const INCREMENTS: usize = 10_000;
+
+//the locks only differ in their guard types, this lets the loops be written once
+trait Lock: Send + Sync {
+    fn increment(&self);
+}
+
+impl Lock for first::SpinLock<usize> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+}
+
+impl Lock for second::SpinLock<usize> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+}
+
+impl Lock for ClhLock<usize> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+}
+
+impl Lock for TicketLock<usize> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+}
+
+impl Lock for Mutex<usize> {
+    fn increment(&self) {
+        *self.lock().unwrap() += 1;
+    }
+}
+
+macro_rules! for_each_lock {
+    ($run:ident, $group:expr $(, $arg:expr)*) => {
+        $run($group, "first::SpinLock", || first::SpinLock::new(0) $(, $arg)*);
+        $run($group, "second::SpinLock (MCS)", || second::SpinLock::new(0) $(, $arg)*);
+        $run($group, "ClhLock", || ClhLock::new(0) $(, $arg)*);
+        $run($group, "TicketLock", || TicketLock::new(0) $(, $arg)*);
+        $run($group, "Mutex", || Mutex::new(0) $(, $arg)*);
+    };
+}
+
+fn run_single<L: Lock>(group: &mut BenchmarkGroup<WallTime>, name: &str, make: fn() -> L) {
+    group.bench_function(name, |b| {
+        let lock = make();
+        b.iter(|| {
+            for _ in 0..INCREMENTS {
+                lock.increment();
+            }
+        });
+    });
+}
+
+fn run_multi<L>(group: &mut BenchmarkGroup<WallTime>, name: &str, make: fn() -> L, threads: usize)
+where
+    L: Lock + 'static,
+{
+    group.bench_function(format!("{} {} threads", name, threads), |b| {
+        b.iter(|| {
+            let lock = Arc::new(make());
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    let lock = lock.clone();
+                    thread::spawn(move || {
+                        for _ in 0..INCREMENTS {
+                            lock.increment();
+                        }
+                    })
+                })
+                .collect();
+            handles.into_iter().for_each(|h| h.join().unwrap());
+        });
+    });
+}
+
+fn bench_uncontended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lock_uncontended");
+    for_each_lock!(run_single, &mut group);
+    group.finish();
+}
+
+fn bench_contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lock_contended");
+    group.sample_size(20);
+    for threads in [2, 4, 8] {
+        for_each_lock!(run_multi, &mut group, threads);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_uncontended, bench_contended);
+criterion_main!(benches);
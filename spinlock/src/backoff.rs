@@ -0,0 +1,24 @@
+//the spin-then-yield policy of `first::SpinLock::lock`:
+//double the spin count on every failed attempt, once it reaches 1024 yield the thread instead
+pub(crate) struct Backoff{
+    step: u32,
+}
+
+impl Backoff{
+
+    #[inline(always)]
+    pub(crate) const fn new() -> Self{
+        Backoff{ step: 1 }
+    }
+
+    pub(crate) fn snooze(&mut self){
+        for _ in 0..self.step{
+            core::hint::spin_loop();
+        }
+        if self.step < 1 << 10{
+            self.step <<= 1;
+        }else{
+            std::thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use spinlock::first::SpinLock;
+use spinlock::ticket::TicketLock;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+
+const NUM_THREADS: usize = 4;
+const ACQUIRES: usize = 20_000;
+
+//longest run of back-to-back acquisitions by one thread, and per thread counts
+fn report(name: &str, order: &[usize]){
+    let mut counts = [0usize; NUM_THREADS];
+    let mut longest = 0;
+    let mut run = 0;
+    for (i, &t) in order.iter().enumerate(){
+        counts[t] += 1;
+        run = if i > 0 && order[i - 1] == t { run + 1 } else { 1 };
+        longest = longest.max(run);
+    }
+    println!("{:<10} per thread {:?}, longest streak {}", name, counts, longest);
+}
+
+
+fn main() {
+    //ticket lock: record (thread, ticket) in the order the lock was taken
+    let lock = Arc::new(TicketLock::new(Vec::with_capacity(NUM_THREADS * ACQUIRES)));
+    let barrier = Arc::new(Barrier::new(NUM_THREADS));
+    let handles: Vec<_> = (0..NUM_THREADS).map(|t| {
+        let lock = Arc::clone(&lock);
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            barrier.wait();
+            for _ in 0..ACQUIRES {
+                let mut guard = lock.lock();
+                let ticket = guard.ticket();
+                guard.push((t, ticket));
+            }
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let log = Arc::try_unwrap(lock).unwrap().into_inner();
+    //served strictly in the order tickets were drawn
+    for (i, &(_, ticket)) in log.iter().enumerate() {
+        assert_eq!(ticket, i, "ticket {} served out of order", ticket);
+    }
+    let ticket_order: Vec<usize> = log.iter().map(|&(t, _)| t).collect();
+    report("TicketLock", &ticket_order);
+
+    //the unfair test-and-set lock for comparison
+    let lock = Arc::new(SpinLock::new(Vec::with_capacity(NUM_THREADS * ACQUIRES)));
+    let barrier = Arc::new(Barrier::new(NUM_THREADS));
+    let handles: Vec<_> = (0..NUM_THREADS).map(|t| {
+        let lock = Arc::clone(&lock);
+        let barrier = Arc::clone(&barrier);
+        thread::spawn(move || {
+            barrier.wait();
+            for _ in 0..ACQUIRES {
+                lock.lock().push(t);
+            }
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    report("SpinLock", &Arc::try_unwrap(lock).unwrap().into_inner());
+
+    println!("Tickets served in FIFO order ✅");
+}
@@ -1,2 +1,5 @@
+mod backoff;
 pub mod first;
+pub mod rwlock;
 pub mod second;
+pub mod ticket;
@@ -0,0 +1,313 @@
+use core::fmt;
+use core::cell::UnsafeCell;
+use core::fmt::Debug;
+use core::mem;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::backoff::Backoff;
+
+const WRITER: usize = 1;
+//a writer is spinning, in writer-preferring mode new readers hold off
+const WRITER_WAITING: usize = 1 << 1;
+//held by the single upgradable reader, shuts out writers but not plain readers
+const UPGRADABLE: usize = 1 << 2;
+//readers are counted above the flag bits
+const READER: usize = 1 << 3;
+
+pub struct RwSpinLock<T>{
+    data: UnsafeCell<T>,
+    state: AtomicUsize,
+    prefer_writers: bool,
+}
+
+
+impl<T> RwSpinLock<T>{
+
+    //readers get in whenever no writer holds the lock,
+    //a steady stream of them can starve writers
+    #[inline(always)]
+    pub const fn new(data: T) -> RwSpinLock<T>{
+        RwSpinLock{
+            data: UnsafeCell::new(data),
+            state: AtomicUsize::new(0),
+            prefer_writers: false,
+        }
+    }
+
+    //new readers wait while a writer is waiting, so writers can't starve
+    #[inline(always)]
+    pub const fn new_writer_preferring(data: T) -> RwSpinLock<T>{
+        RwSpinLock{
+            data: UnsafeCell::new(data),
+            state: AtomicUsize::new(0),
+            prefer_writers: true,
+        }
+    }
+
+    #[inline(always)]
+    fn blocks_readers(&self, state: usize) -> bool{
+        state & WRITER != 0 || (self.prefer_writers && state & WRITER_WAITING != 0)
+    }
+
+    #[must_use]
+    pub fn read(&self) -> RwSpinLockReadGuard<'_, T>{
+        let mut backoff = Backoff::new();
+        loop{
+            if let Some(guard) = self.try_read(){
+                return guard;
+            }
+            backoff.snooze();
+        }
+    }
+
+    //fails only if a writer holds the lock, or is waiting for it in writer-preferring mode
+    pub fn try_read(&self) -> Option<RwSpinLockReadGuard<'_, T>>{
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop{
+            if self.blocks_readers(state){
+                return None;
+            }
+            match self.state.compare_exchange_weak(state, state + READER, Ordering::Acquire, Ordering::Relaxed){
+                Ok(_) => return Some(RwSpinLockReadGuard{ lock: self }),
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn write(&self) -> RwSpinLockWriteGuard<'_, T>{
+        let mut backoff = Backoff::new();
+        loop{
+            if let Some(guard) = self.try_write(){
+                return guard;
+            }
+            if self.prefer_writers{
+                self.state.fetch_or(WRITER_WAITING, Ordering::Relaxed);
+            }
+            backoff.snooze();
+        }
+    }
+
+    pub fn try_write(&self) -> Option<RwSpinLockWriteGuard<'_, T>>{
+        let mut state = self.state.load(Ordering::Relaxed);
+        //the waiting bit is cleared on success, writers still spinning set it again
+        while state & !WRITER_WAITING == 0{
+            match self.state.compare_exchange_weak(state, WRITER, Ordering::Acquire, Ordering::Relaxed){
+                Ok(_) => return Some(RwSpinLockWriteGuard{ lock: self }),
+                Err(s) => state = s,
+            }
+        }
+        None
+    }
+
+    //shares the lock with plain readers, but only one upgradable reader is let in at a time
+    //and writers stay out until it is dropped, so `upgrade` sees the data it read
+    #[must_use]
+    pub fn upgradable_read(&self) -> RwSpinLockUpgradableGuard<'_, T>{
+        let mut backoff = Backoff::new();
+        loop{
+            if let Some(guard) = self.try_upgradable_read(){
+                return guard;
+            }
+            backoff.snooze();
+        }
+    }
+
+    pub fn try_upgradable_read(&self) -> Option<RwSpinLockUpgradableGuard<'_, T>>{
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop{
+            if self.blocks_readers(state) || state & UPGRADABLE != 0{
+                return None;
+            }
+            match self.state.compare_exchange_weak(state, state | UPGRADABLE, Ordering::Acquire, Ordering::Relaxed){
+                Ok(_) => return Some(RwSpinLockUpgradableGuard{ lock: self }),
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool{
+        self.state.load(Ordering::Relaxed) & !WRITER_WAITING != 0
+    }
+
+    #[inline(always)]
+    pub fn is_write_locked(&self) -> bool{
+        self.state.load(Ordering::Relaxed) & WRITER != 0
+    }
+
+    pub fn reader_count(&self) -> usize{
+        self.state.load(Ordering::Relaxed) / READER
+    }
+
+    pub fn into_inner(self) -> T{
+        self.data.into_inner()
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T{
+        self.data.get_mut()
+    }
+
+}
+
+
+
+
+pub struct RwSpinLockReadGuard<'a, T>{
+    lock: &'a RwSpinLock<T>,
+}
+
+pub struct RwSpinLockWriteGuard<'a, T>{
+    lock: &'a RwSpinLock<T>,
+}
+
+pub struct RwSpinLockUpgradableGuard<'a, T>{
+    lock: &'a RwSpinLock<T>,
+}
+
+
+impl<'a, T> RwSpinLockUpgradableGuard<'a, T>{
+
+    //waits for the plain readers to leave. the upgradable bit is only
+    //traded for the writer bit, no other writer can get in between
+    pub fn upgrade(self) -> RwSpinLockWriteGuard<'a, T>{
+        let mut backoff = Backoff::new();
+        let mut this = self;
+        loop{
+            match this.try_upgrade(){
+                Ok(guard) => return guard,
+                Err(guard) => this = guard,
+            }
+            if this.lock.prefer_writers{
+                this.lock.state.fetch_or(WRITER_WAITING, Ordering::Relaxed);
+            }
+            backoff.snooze();
+        }
+    }
+
+    //hands the guard back if plain readers are still inside
+    pub fn try_upgrade(self) -> Result<RwSpinLockWriteGuard<'a, T>, Self>{
+        let lock = self.lock;
+        let mut state = lock.state.load(Ordering::Relaxed);
+        while state & !WRITER_WAITING == UPGRADABLE{
+            match lock.state.compare_exchange_weak(state, WRITER, Ordering::Acquire, Ordering::Relaxed){
+                Ok(_) => {
+                    mem::forget(self);
+                    return Ok(RwSpinLockWriteGuard{ lock });
+                }
+                Err(s) => state = s,
+            }
+        }
+        Err(self)
+    }
+
+    //becomes a plain reader, letting the next upgradable reader in
+    pub fn downgrade(self) -> RwSpinLockReadGuard<'a, T>{
+        let lock = self.lock;
+        mem::forget(self);
+        //a single update, there is no moment where nobody holds the lock
+        lock.state.fetch_add(READER - UPGRADABLE, Ordering::Release);
+        RwSpinLockReadGuard{ lock }
+    }
+}
+
+impl<'a, T> RwSpinLockWriteGuard<'a, T>{
+
+    //keeps the lock for reading, no writer can get in before we read again
+    pub fn downgrade(self) -> RwSpinLockReadGuard<'a, T>{
+        let lock = self.lock;
+        mem::forget(self);
+        lock.state.fetch_add(READER - WRITER, Ordering::Release);
+        RwSpinLockReadGuard{ lock }
+    }
+
+    pub fn downgrade_to_upgradable(self) -> RwSpinLockUpgradableGuard<'a, T>{
+        let lock = self.lock;
+        mem::forget(self);
+        lock.state.fetch_add(UPGRADABLE - WRITER, Ordering::Release);
+        RwSpinLockUpgradableGuard{ lock }
+    }
+}
+
+
+impl<T> std::ops::Deref for RwSpinLockReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> std::ops::Deref for RwSpinLockUpgradableGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> std::ops::Deref for RwSpinLockWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> std::ops::DerefMut for RwSpinLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for RwSpinLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.fetch_sub(READER, Ordering::Release);
+    }
+}
+
+impl<T> Drop for RwSpinLockUpgradableGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.fetch_sub(UPGRADABLE, Ordering::Release);
+    }
+}
+
+impl<T> Drop for RwSpinLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        //keep a waiting writer's bit
+        self.lock.state.fetch_and(!WRITER, Ordering::Release);
+    }
+}
+
+impl<T: Debug> Debug for RwSpinLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_read() {
+            Some(g) => f.debug_tuple("RwSpinLock").field(&*g).finish(),
+            None    => f.write_str("RwSpinLock(<locked>)"),
+        }
+    }
+}
+
+impl<T: Debug> Debug for RwSpinLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Debug> Debug for RwSpinLockUpgradableGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Debug> Debug for RwSpinLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Default> Default for RwSpinLock<T>{
+    fn default() -> Self{
+        Self::new(T::default())
+    }
+}
+
+unsafe impl<T: Send> Send for RwSpinLock<T>{}
+unsafe impl<T: Send + Sync> Sync for RwSpinLock<T>{}
@@ -1,28 +1,73 @@
 use core::fmt;
-use core::cell::UnsafeCell;
+use core::cell::{RefCell, UnsafeCell};
 use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ptr::{self, NonNull};
 use core::sync::atomic::{AtomicBool,AtomicPtr, Ordering};
-use std::thread;
-use std::thread::{Thread};
+use crate::backoff::Backoff;
 
 pub struct SpinLock<T>{
     data: UnsafeCell<T>,
     tail: Link,//track callers, to make lock access fair,
 }
 
-type Link = AtomicPtr<Node>;
+type Link = AtomicPtr<McsNode>;
 
-struct Node{
-    handle: Thread,
+//a waiter's place in the queue. every waiter spins on its own node until
+//the predecessor hands the lock over by clearing `locked`
+pub struct McsNode{
     next: Link,
     locked: AtomicBool,
 }
 
+impl McsNode{
+
+    #[inline(always)]
+    pub const fn new() -> Self{
+        McsNode{
+            next: AtomicPtr::new(ptr::null_mut()),
+            locked: AtomicBool::new(false),
+        }
+    }
+
+    #[inline(always)]
+    fn reset(&mut self){
+        *self.next.get_mut() = ptr::null_mut();
+        *self.locked.get_mut() = true;
+    }
+}
+
+impl Default for McsNode{
+    fn default() -> Self{
+        Self::new()
+    }
+}
+
+thread_local!{
+    //nodes of this thread's finished acquisitions, one per nesting level ever reached.
+    //boxed, a node must keep its address while other threads link to it
+    #[allow(clippy::vec_box)]
+    static NODES: RefCell<Vec<Box<McsNode>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn cached_node() -> NonNull<McsNode>{
+    //`with` would panic once the cache is torn down, a lock taken from a later destructor gets a fresh node
+    let node = NODES.try_with(|nodes| nodes.borrow_mut().pop()).ok().flatten().unwrap_or_default();
+    NonNull::from(Box::leak(node))
+}
+
+//no other thread references the node once the lock was handed on
+unsafe fn recycle(node: NonNull<McsNode>){
+    let node = unsafe{ Box::from_raw(node.as_ptr()) };
+    //during thread teardown the cache may be gone already, just free the node
+    let _ = NODES.try_with(|nodes| nodes.borrow_mut().push(node));
+}
+
 
 impl<T> SpinLock<T>{
 
     #[inline(always)]
-    pub fn new(data: T) -> Self{
+    pub const fn new(data: T) -> Self{
         
         SpinLock{
             data: UnsafeCell::new(data),
@@ -30,68 +75,111 @@ impl<T> SpinLock<T>{
         }
     }
 
+    //takes the queue node from a per-thread cache, only the first acquisition at a nesting depth allocates
     #[must_use]
     pub fn lock(&self) -> SpinLockGuard<'_, T>{
-        let node = Node{
-                    handle: thread::current(),
-                    next: AtomicPtr::new(core::ptr::null_mut()),
-                    locked: AtomicBool::new(true),
-            };
-        let boxed_new = Box::into_raw(Box::new(node));
-        let node_ptr = self.tail.swap(boxed_new, Ordering::Release);
-
-        if !node_ptr.is_null(){
-                unsafe{
-                    (*node_ptr).next.store(boxed_new, Ordering::Release);
-                    let mut s: u8 = 0b0110_0100;
-                    while (*boxed_new).locked.load(Ordering::Acquire){
-                        if s > 0{
-                            core::hint::spin_loop();
-                            s -= 1;
-                        }
-                        thread::park();
-                        core::hint::spin_loop();
-                    }
-                };
-            }else{
-                unsafe{
-                    (*boxed_new).locked.store(false, Ordering::Release);
-                }
-            }
+        let node = cached_node();
+        unsafe{ self.acquire(node) };
+        SpinLockGuard{
+            lock: self,
+            node,
+            cached: true,
+            _node: PhantomData,
+        }
+    }
 
-        println!("Thread {:?} acquired the lock", thread::current().id());
+    /// Queues on a node supplied by the caller, which stays borrowed until the guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The guard must be dropped before `node` is moved or freed. Leaking it (`mem::forget`,
+    /// an `Rc` cycle) leaves `node` linked into the queue, and the next waiter writes through it.
+    #[must_use]
+    pub unsafe fn lock_with<'a>(&'a self, node: &'a mut McsNode) -> SpinLockGuard<'a, T>{
+        let node = NonNull::from(node);
+        unsafe{ self.acquire(node) };
         SpinLockGuard{
             lock: self,
-            node: boxed_new, 
+            node,
+            cached: false,
+            _node: PhantomData,
         }
     }
 
     pub fn try_lock(&self) -> Result<SpinLockGuard<'_, T>, ()>{
-        let node = Box::into_raw(Box::new(Node{
-            handle: thread::current(),
-            locked: false.into(),
-            next: AtomicPtr::new(core::ptr::null_mut()),
-        }));
-
-        match self.tail.compare_exchange(
-            core::ptr::null_mut(),
+        let node = cached_node();
+        if unsafe{ self.try_acquire(node) }{
+            Ok(SpinLockGuard{
+                lock: self,
+                node,
+                cached: true,
+                _node: PhantomData,
+            })
+        }else{
+            unsafe{ recycle(node) };
+            Err(())
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`lock_with`](Self::lock_with), a returned guard must not be leaked.
+    pub unsafe fn try_lock_with<'a>(&'a self, node: &'a mut McsNode) -> Option<SpinLockGuard<'a, T>>{
+        let node = NonNull::from(node);
+        if !unsafe{ self.try_acquire(node) }{
+            return None;
+        }
+        Some(SpinLockGuard{
+            lock: self,
             node,
-            Ordering::AcqRel,
-            Ordering::Relaxed,
-        )
-        {
-            Ok(_) => Ok(SpinLockGuard{lock: self,node }),
-            Err(_) => {
-                unsafe{
-                    drop(Box::from_raw(node));
+            cached: false,
+            _node: PhantomData,
+        })
+    }
+
+    //caller owns `node` exclusively until it is released
+    unsafe fn acquire(&self, node: NonNull<McsNode>){
+        let node = node.as_ptr();
+        unsafe{ (*node).reset() };
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+
+        if !prev.is_null(){
+            unsafe{
+                (*prev).next.store(node, Ordering::Release);
+                let mut backoff = Backoff::new();
+                while (*node).locked.load(Ordering::Acquire){
+                    backoff.snooze();
                 }
-                Err(())
             }
         }
+    }
+
+    //the node goes in exactly like for `lock`, with `locked` set and no successor,
+    //so whoever queues behind us waits for the handover in `release`
+    unsafe fn try_acquire(&self, node: NonNull<McsNode>) -> bool{
+        let node = node.as_ptr();
+        unsafe{ (*node).reset() };
+        self.tail.compare_exchange(
+            ptr::null_mut(),
+            node,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        ).is_ok()
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool{
+        !self.tail.load(Ordering::Relaxed).is_null()
+    }
 
+    pub fn into_inner(self) -> T{
+        self.data.into_inner()
     }
 
-     
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T{
+        self.data.get_mut()
+    }
 
 }
 
@@ -104,33 +192,34 @@ impl<T> SpinLock<T>{
 
 pub struct SpinLockGuard<'a, T>{
     lock: &'a SpinLock<T>,
-    node: *mut Node,
+    node: NonNull<McsNode>,
+    //the node came from the thread's cache and goes back there
+    cached: bool,
+    _node: PhantomData<&'a mut McsNode>,
 }
 
 impl<'a, T> SpinLockGuard<'a, T>{
     fn unlock(&mut self){
+        let node = self.node.as_ptr();
         unsafe{
-            let next = (*self.node).next.load(Ordering::Acquire);
+            let mut next = (*node).next.load(Ordering::Acquire);
             if next.is_null(){
                 if self.lock.tail.compare_exchange(
-                    self.node,
+                    node,
                     core::ptr::null_mut(),
                     Ordering::Release,
                     Ordering::Relaxed,
                 ).is_ok(){
-                    (*self.node).locked.store(true, Ordering::Release);
-                }else{
-                    while (*self.node).next.load(Ordering::Acquire).is_null(){
-                        core::hint::spin_loop();
-                    }
-                    let next = (*self.node).next.load(Ordering::Acquire);
-                    (*next).locked.store(false, Ordering::Release);
-                    (*next).handle.unpark();
+                    return;
+                }
+                //a successor swapped itself in but hasn't linked up yet
+                while next.is_null(){
+                    core::hint::spin_loop();
+                    next = (*node).next.load(Ordering::Acquire);
                 }
-            }else{
-                (*next).locked.store(false, Ordering::Relaxed);
-                (*next).handle.unpark();
             }
+            //the successor may reuse its node right after this, don't touch it again
+            (*next).locked.store(false, Ordering::Release);
         }
 
     }
@@ -153,9 +242,18 @@ impl<'a, T> std::ops::DerefMut for SpinLockGuard<'a, T> {
 
 impl<'a, T> Drop for SpinLockGuard<'a, T> {
     fn drop(&mut self) {
-        unsafe{
-            self.unlock();
-            drop(Box::from_raw(self.node));
+        self.unlock();
+        if self.cached{
+            unsafe{ recycle(self.node) };
+        }
+    }
+}
+
+impl<T: Debug> Debug for SpinLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Ok(g)  => f.debug_tuple("SpinLock").field(&*g).finish(),
+            Err(_) => f.write_str("SpinLock(<locked>)"),
         }
     }
 }
@@ -165,3 +263,182 @@ unsafe impl<T: Send> Send for SpinLock<T>{}
 unsafe impl<T: Send> Sync for SpinLock<T>{}
 
 
+
+
+//CLH queue lock. each waiter spins on its predecessor's node instead of its own.
+//on release a thread leaves its node to the successor spinning on it and keeps
+//the predecessor's node for its next acquisition, so nodes circulate without allocating
+pub struct ClhLock<T>{
+    data: UnsafeCell<T>,
+    //never null. tagged with FREE when it points at a released node, then nobody
+    //waits on it and the lock can be taken without looking at the node itself
+    tail: AtomicPtr<ClhNode>,
+}
+
+const FREE: usize = 1;
+
+#[repr(align(64))]
+struct ClhNode{
+    locked: AtomicBool,
+}
+
+impl ClhNode{
+    fn alloc() -> Box<ClhNode>{
+        Box::new(ClhNode{ locked: AtomicBool::new(false) })
+    }
+}
+
+thread_local!{
+    //boxed, a node must keep its address while other threads spin on it
+    #[allow(clippy::vec_box)]
+    static CLH_NODES: RefCell<Vec<Box<ClhNode>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn cached_clh_node() -> NonNull<ClhNode>{
+    let node = CLH_NODES.try_with(|nodes| nodes.borrow_mut().pop()).ok().flatten().unwrap_or_else(ClhNode::alloc);
+    NonNull::from(Box::leak(node))
+}
+
+unsafe fn recycle_clh(node: NonNull<ClhNode>){
+    let node = unsafe{ Box::from_raw(node.as_ptr()) };
+    let _ = CLH_NODES.try_with(|nodes| nodes.borrow_mut().push(node));
+}
+
+
+impl<T> ClhLock<T>{
+
+    pub fn new(data: T) -> Self{
+        let node = Box::into_raw(ClhNode::alloc());
+        ClhLock{
+            data: UnsafeCell::new(data),
+            tail: AtomicPtr::new(node.map_addr(|a| a | FREE)),
+        }
+    }
+
+    #[must_use]
+    pub fn lock(&self) -> ClhLockGuard<'_, T>{
+        let node = cached_clh_node();
+        unsafe{ node.as_ref() }.locked.store(true, Ordering::Relaxed);
+        let pred = self.tail.swap(node.as_ptr(), Ordering::AcqRel);
+
+        if pred.addr() & FREE == 0{
+            let mut backoff = Backoff::new();
+            while unsafe{ (*pred).locked.load(Ordering::Acquire) }{
+                backoff.snooze();
+            }
+        }
+        ClhLockGuard{
+            lock: self,
+            node,
+            pred: unsafe{ NonNull::new_unchecked(pred.map_addr(|a| a & !FREE)) },
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<ClhLockGuard<'_, T>>{
+        let pred = self.tail.load(Ordering::Relaxed);
+        if pred.addr() & FREE == 0{
+            return None;
+        }
+
+        let node = cached_clh_node();
+        unsafe{ node.as_ref() }.locked.store(true, Ordering::Relaxed);
+        if self.tail.compare_exchange(pred, node.as_ptr(), Ordering::AcqRel, Ordering::Relaxed).is_err(){
+            unsafe{ recycle_clh(node) };
+            return None;
+        }
+        Some(ClhLockGuard{
+            lock: self,
+            node,
+            pred: unsafe{ NonNull::new_unchecked(pred.map_addr(|a| a & !FREE)) },
+        })
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool{
+        self.tail.load(Ordering::Relaxed).addr() & FREE == 0
+    }
+
+    pub fn into_inner(self) -> T{
+        let this = core::mem::ManuallyDrop::new(self);
+        unsafe{
+            drop(Box::from_raw(this.tail.load(Ordering::Relaxed).map_addr(|a| a & !FREE)));
+            ptr::read(&this.data).into_inner()
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T{
+        self.data.get_mut()
+    }
+
+}
+
+impl<T> Drop for ClhLock<T>{
+    fn drop(&mut self){
+        //no guard is alive, so the tail is a released node owned by the lock
+        let tail = self.tail.get_mut().map_addr(|a| a & !FREE);
+        drop(unsafe{ Box::from_raw(tail) });
+    }
+}
+
+
+
+
+pub struct ClhLockGuard<'a, T>{
+    lock: &'a ClhLock<T>,
+    node: NonNull<ClhNode>,
+    pred: NonNull<ClhNode>,
+}
+
+
+impl<'a, T> std::ops::Deref for ClhLockGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> std::ops::DerefMut for ClhLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T> Drop for ClhLockGuard<'a, T> {
+    fn drop(&mut self) {
+        let node = self.node.as_ptr();
+        unsafe{
+            //nobody queued behind us: mark our node as the released tail.
+            //otherwise hand over through the flag the successor spins on.
+            //either way the node isn't ours anymore afterwards
+            if self.lock.tail.compare_exchange(
+                node,
+                node.map_addr(|a| a | FREE),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ).is_err(){
+                (*node).locked.store(false, Ordering::Release);
+            }
+            //nobody looks at the predecessor's node anymore
+            recycle_clh(self.pred);
+        }
+    }
+}
+
+impl<T: Debug> Debug for ClhLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Some(g) => f.debug_tuple("ClhLock").field(&*g).finish(),
+            None    => f.write_str("ClhLock(<locked>)"),
+        }
+    }
+}
+
+impl<T: Default> Default for ClhLock<T>{
+    fn default() -> Self{
+        Self::new(T::default())
+    }
+}
+
+unsafe impl<T: Send> Send for ClhLock<T>{}
+unsafe impl<T: Send> Sync for ClhLock<T>{}
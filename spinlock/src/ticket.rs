@@ -0,0 +1,145 @@
+use core::fmt;
+use core::cell::UnsafeCell;
+use core::fmt::Debug;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+//spins per thread queued ahead of us before looking at `serving` again
+const SPIN_PER_WAITER: usize = 32;
+//rounds before we start yielding, whoever is ahead may have been preempted
+const YIELD_AFTER: u32 = 64;
+
+//threads take a ticket and are served in ticket order, so the lock is handed out first come first served
+pub struct TicketLock<T>{
+    data: UnsafeCell<T>,
+    next: AtomicUsize,
+    serving: AtomicUsize,
+}
+
+
+impl<T> TicketLock<T>{
+
+    #[inline(always)]
+    pub const fn new(data: T) -> TicketLock<T>{
+        TicketLock{
+            data: UnsafeCell::new(data),
+            next: AtomicUsize::new(0),
+            serving: AtomicUsize::new(0),
+        }
+    }
+
+    #[must_use]
+    pub fn lock(&self) -> TicketLockGuard<'_, T>{
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut rounds = 0u32;
+
+        loop{
+            let serving = self.serving.load(Ordering::Acquire);
+            if serving == ticket{
+                break;
+            }
+            //the further back in line, the longer until our turn
+            let distance = ticket.wrapping_sub(serving);
+            for _ in 0..distance.saturating_mul(SPIN_PER_WAITER){
+                core::hint::spin_loop();
+            }
+            if rounds < YIELD_AFTER{
+                rounds += 1;
+            }else{
+                std::thread::yield_now();
+            }
+        }
+        TicketLockGuard{
+            lock: self,
+            ticket,
+        }
+    }
+
+    //only succeeds if nobody holds or waits for the lock
+    pub fn try_lock(&self) -> Option<TicketLockGuard<'_, T>>{
+        //pairs with the release of the previous holder, the cas on `next` doesn't
+        let ticket = self.serving.load(Ordering::Acquire);
+        self.next.compare_exchange(ticket, ticket.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed).ok()?;
+        Some(TicketLockGuard{
+            lock: self,
+            ticket,
+        })
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self) -> bool{
+        self.next.load(Ordering::Relaxed) != self.serving.load(Ordering::Relaxed)
+    }
+
+    //threads queued behind the holder, a racy snapshot
+    pub fn waiters(&self) -> usize{
+        let serving = self.serving.load(Ordering::Relaxed);
+        let next = self.next.load(Ordering::Relaxed);
+        next.wrapping_sub(serving).saturating_sub(1)
+    }
+
+    pub fn into_inner(self) -> T{
+        self.data.into_inner()
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T{
+        self.data.get_mut()
+    }
+
+}
+
+
+
+
+pub struct TicketLockGuard<'a, T>{
+    lock: &'a TicketLock<T>,
+    ticket: usize,
+}
+
+impl<T> TicketLockGuard<'_, T>{
+
+    //position of this acquisition in the lock's order, wraps around on overflow
+    #[inline(always)]
+    pub fn ticket(&self) -> usize{
+        self.ticket
+    }
+}
+
+
+impl<T> std::ops::Deref for TicketLockGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> std::ops::DerefMut for TicketLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for TicketLockGuard<'_, T> {
+    fn drop(&mut self) {
+        //only the holder moves `serving`
+        self.lock.serving.store(self.ticket.wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl<T: Debug> Debug for TicketLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Some(g) => f.debug_tuple("TicketLock").field(&*g).finish(),
+            None    => f.write_str("TicketLock(<locked>)"),
+        }
+    }
+}
+
+impl<T: Default> Default for TicketLock<T>{
+    fn default() -> Self{
+        Self::new(T::default())
+    }
+}
+
+unsafe impl<T: Send> Send for TicketLock<T>{}
+unsafe impl<T: Send> Sync for TicketLock<T>{}
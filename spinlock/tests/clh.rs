@@ -0,0 +1,114 @@
+use spinlock::second::ClhLock;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn lock_and_try_lock_under_contention() {
+    const THREADS: usize = 6;
+    const ROUNDS: usize = 10_000;
+
+    let lock = Arc::new(ClhLock::new(0usize));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    if i % 2 == 0 {
+                        *lock.lock() += 1;
+                    } else {
+                        loop {
+                            if let Some(mut guard) = lock.try_lock() {
+                                *guard += 1;
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert!(!lock.is_locked());
+    assert_eq!(
+        Arc::try_unwrap(lock).unwrap().into_inner(),
+        THREADS * ROUNDS
+    );
+}
+
+#[test]
+fn try_lock_fails_while_held_or_queued() {
+    let lock = Arc::new(ClhLock::new(0));
+    assert!(!lock.is_locked());
+    let guard = lock.try_lock().unwrap();
+    assert!(lock.is_locked());
+    assert!(lock.try_lock().is_none());
+
+    let acquired = Arc::new(AtomicBool::new(false));
+    let waiter = {
+        let (lock, acquired) = (lock.clone(), acquired.clone());
+        thread::spawn(move || {
+            *lock.lock() += 1;
+            acquired.store(true, Ordering::Release);
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert!(!acquired.load(Ordering::Acquire));
+    drop(guard);
+    waiter.join().unwrap();
+    assert!(!lock.is_locked());
+    assert_eq!(*lock.try_lock().unwrap(), 1);
+}
+
+#[test]
+fn nodes_outlive_their_threads() {
+    //nodes migrate between threads, exiting threads free the ones they hold
+    let lock = Arc::new(ClhLock::new(Vec::new()));
+    for round in 0..20 {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        lock.lock().push(round * 4 + i);
+                        drop(lock.try_lock());
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+    }
+    let a = ClhLock::new(1);
+    let ga = a.lock();
+    let gb = lock.lock();
+    assert_eq!(gb.len(), 8_000);
+    assert_eq!(*ga, 1);
+}
+
+#[test]
+fn lock_from_a_thread_local_destructor() {
+    struct LockOnExit(Arc<ClhLock<usize>>);
+    impl Drop for LockOnExit {
+        fn drop(&mut self) {
+            *self.0.lock() += 1;
+            *self.0.try_lock().unwrap() += 1;
+        }
+    }
+    thread_local! {
+        static ON_EXIT: RefCell<Option<LockOnExit>> = const { RefCell::new(None) };
+    }
+
+    let lock = Arc::new(ClhLock::new(0));
+    let lock2 = lock.clone();
+    thread::spawn(move || {
+        //registered before the node cache, so it tends to be destroyed after it
+        ON_EXIT.with(|e| *e.borrow_mut() = Some(LockOnExit(lock2.clone())));
+        *lock2.lock() += 1;
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*lock.lock(), 3);
+}
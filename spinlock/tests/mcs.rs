@@ -0,0 +1,114 @@
+use spinlock::second::{McsNode, SpinLock};
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn cached_and_caller_nodes_mix() {
+    const THREADS: usize = 6;
+    const ROUNDS: usize = 10_000;
+
+    let lock = Arc::new(SpinLock::new(0usize));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                let mut node = McsNode::new();
+                for _ in 0..ROUNDS {
+                    match i % 3 {
+                        0 => *lock.lock() += 1,
+                        1 => *unsafe { lock.lock_with(&mut node) } += 1,
+                        _ => loop {
+                            if let Ok(mut guard) = lock.try_lock() {
+                                *guard += 1;
+                                break;
+                            }
+                            thread::yield_now();
+                        },
+                    }
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(*lock.lock(), THREADS * ROUNDS);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn try_lock_holder_hands_over_to_waiter() {
+    let lock = Arc::new(SpinLock::new(0));
+    let guard = lock.try_lock().unwrap();
+    assert!(lock.try_lock().is_err());
+
+    let acquired = Arc::new(AtomicBool::new(false));
+    let waiter = {
+        let (lock, acquired) = (lock.clone(), acquired.clone());
+        thread::spawn(move || {
+            let mut node = McsNode::new();
+            *unsafe { lock.lock_with(&mut node) } += 1;
+            acquired.store(true, Ordering::Release);
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert!(!acquired.load(Ordering::Acquire));
+    drop(guard);
+    waiter.join().unwrap();
+    assert_eq!(*lock.lock(), 1);
+}
+
+#[test]
+fn nested_locks_take_separate_nodes() {
+    let a = SpinLock::new(1);
+    let b = SpinLock::new(2);
+    for _ in 0..3 {
+        let ga = a.lock();
+        let gb = b.lock();
+        assert!(a.try_lock().is_err());
+        assert_eq!(*ga + *gb, 3);
+    }
+    assert!(!a.is_locked() && !b.is_locked());
+}
+
+#[test]
+fn try_lock_with_reuses_the_callers_node() {
+    let lock = SpinLock::new(0);
+    let mut node = McsNode::new();
+    for i in 0..3 {
+        let mut guard = unsafe { lock.try_lock_with(&mut node) }.unwrap();
+        assert!(lock.try_lock().is_err());
+        assert_eq!(*guard, i);
+        *guard += 1;
+    }
+    let held = lock.lock();
+    assert!(unsafe { lock.try_lock_with(&mut node) }.is_none());
+    drop(held);
+    assert_eq!(*lock.lock(), 3);
+}
+
+#[test]
+fn lock_from_a_thread_local_destructor() {
+    struct LockOnExit(Arc<SpinLock<usize>>);
+    impl Drop for LockOnExit {
+        fn drop(&mut self) {
+            *self.0.lock() += 1;
+            *self.0.try_lock().unwrap() += 1;
+        }
+    }
+    thread_local! {
+        static ON_EXIT: RefCell<Option<LockOnExit>> = const { RefCell::new(None) };
+    }
+
+    let lock = Arc::new(SpinLock::new(0));
+    let lock2 = lock.clone();
+    thread::spawn(move || {
+        //registered before the node cache, so it tends to be destroyed after it
+        ON_EXIT.with(|e| *e.borrow_mut() = Some(LockOnExit(lock2.clone())));
+        *lock2.lock() += 1;
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*lock.lock(), 3);
+}
@@ -0,0 +1,162 @@
+use spinlock::rwlock::RwSpinLock;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+#[test]
+fn readers_share_writers_exclude() {
+    let lock = RwSpinLock::new(1);
+    let r1 = lock.read();
+    let r2 = lock.try_read().unwrap();
+    assert_eq!(lock.reader_count(), 2);
+    assert!(lock.try_write().is_none());
+    drop((r1, r2));
+
+    let mut w = lock.write();
+    *w += 1;
+    assert!(lock.is_write_locked());
+    assert!(lock.try_read().is_none());
+    assert!(lock.try_upgradable_read().is_none());
+    drop(w);
+    assert!(!lock.is_locked());
+    assert_eq!(lock.into_inner(), 2);
+}
+
+#[test]
+fn writes_are_exclusive() {
+    const THREADS: usize = 6;
+    const ROUNDS: usize = 5_000;
+
+    for lock in [
+        RwSpinLock::new((0usize, 0usize)),
+        RwSpinLock::new_writer_preferring((0, 0)),
+    ] {
+        let lock = Arc::new(lock);
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..ROUNDS {
+                        if i % 2 == 0 {
+                            let mut w = lock.write();
+                            w.0 += 1;
+                            w.1 += 1;
+                        } else {
+                            let r = lock.read();
+                            assert_eq!(r.0, r.1, "a reader saw a half-done write");
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(lock.read().0, THREADS / 2 * ROUNDS);
+    }
+}
+
+#[test]
+fn one_upgradable_alongside_readers() {
+    let lock = RwSpinLock::new(0);
+    let u = lock.upgradable_read();
+    let r = lock.try_read().unwrap();
+    assert!(lock.try_upgradable_read().is_none());
+    assert!(lock.try_write().is_none());
+
+    //a plain reader is still inside
+    let u = u.try_upgrade().unwrap_err();
+    drop(r);
+    let mut w = u.try_upgrade().ok().unwrap();
+    *w = 5;
+    let u = w.downgrade_to_upgradable();
+    assert!(lock.try_read().is_some());
+    let r = u.downgrade();
+    assert!(lock.try_upgradable_read().is_some());
+    assert_eq!(*r, 5);
+    drop(r);
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn no_writer_between_read_and_upgrade() {
+    const THREADS: usize = 6;
+    const ROUNDS: usize = 5_000;
+
+    for lock in [
+        RwSpinLock::new(0usize),
+        RwSpinLock::new_writer_preferring(0),
+    ] {
+        let lock = Arc::new(lock);
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..ROUNDS {
+                        match i % 3 {
+                            0 => *lock.write() += 1,
+                            1 => {
+                                let u = lock.upgradable_read();
+                                let seen = *u;
+                                let mut w = u.upgrade();
+                                assert_eq!(*w, seen, "a writer got in during upgrade");
+                                *w = seen + 1;
+                            }
+                            _ => drop(lock.read()),
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(*lock.read(), 2 * THREADS / 3 * ROUNDS);
+    }
+}
+
+#[test]
+fn no_writer_between_write_and_downgrade() {
+    const THREADS: usize = 4;
+    const ROUNDS: usize = 5_000;
+
+    let lock = Arc::new(RwSpinLock::new(0usize));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for round in 0..ROUNDS {
+                    let mut w = lock.write();
+                    let mine = i * ROUNDS + round;
+                    *w = mine;
+                    let r = w.downgrade();
+                    for _ in 0..10 {
+                        assert_eq!(*r, mine, "a writer got in during downgrade");
+                        core::hint::spin_loop();
+                    }
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert!(!lock.is_locked());
+}
+
+#[test]
+fn waiting_writer_holds_off_new_readers() {
+    let lock = Arc::new(RwSpinLock::new_writer_preferring(0));
+    let reader = lock.read();
+    let done = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let (lock, done) = (lock.clone(), done.clone());
+        thread::spawn(move || {
+            *lock.write() = 1;
+            done.store(true, Ordering::Release);
+        })
+    };
+
+    //once the writer announced itself, readers queue behind it
+    while lock.try_read().is_some() {
+        thread::yield_now();
+    }
+    assert!(!done.load(Ordering::Acquire));
+    drop(reader);
+    writer.join().unwrap();
+    assert_eq!(*lock.read(), 1);
+}
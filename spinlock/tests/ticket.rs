@@ -0,0 +1,98 @@
+use spinlock::ticket::TicketLock;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn waiters_are_served_in_arrival_order() {
+    const THREADS: usize = 5;
+
+    let lock = Arc::new(TicketLock::new(Vec::new()));
+    let guard = lock.lock();
+    assert_eq!(guard.ticket(), 0);
+
+    //each thread only starts once the previous one is queued, so arrival order is spawn order
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let handle = {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    let mut order = lock.lock();
+                    assert_eq!(order.ticket(), i + 1);
+                    order.push(i);
+                })
+            };
+            while lock.waiters() < i + 1 {
+                thread::yield_now();
+            }
+            handle
+        })
+        .collect();
+
+    assert_eq!(lock.waiters(), THREADS);
+    drop(guard);
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(lock.waiters(), 0);
+    assert_eq!(*lock.lock(), (0..THREADS).collect::<Vec<_>>());
+}
+
+#[test]
+fn try_lock_fails_while_held_or_queued() {
+    let lock = Arc::new(TicketLock::new(0));
+    assert!(!lock.is_locked());
+    assert_eq!(lock.waiters(), 0);
+
+    let guard = lock.try_lock().unwrap();
+    assert!(lock.is_locked());
+    assert_eq!(lock.waiters(), 0);
+    assert!(lock.try_lock().is_none());
+
+    let waiter = {
+        let lock = lock.clone();
+        thread::spawn(move || *lock.lock() += 1)
+    };
+    while lock.waiters() == 0 {
+        thread::yield_now();
+    }
+    drop(guard);
+    waiter.join().unwrap();
+
+    assert!(!lock.is_locked());
+    let guard = lock.try_lock().unwrap();
+    assert_eq!(*guard, 1);
+    assert_eq!(guard.ticket(), 2);
+}
+
+#[test]
+fn counts_under_contention() {
+    const THREADS: usize = 6;
+    const ROUNDS: usize = 10_000;
+
+    let lock = Arc::new(TicketLock::new(0usize));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    if i % 2 == 0 {
+                        *lock.lock() += 1;
+                    } else {
+                        loop {
+                            if let Some(mut guard) = lock.try_lock() {
+                                *guard += 1;
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert!(!lock.is_locked());
+    assert_eq!(lock.waiters(), 0);
+    assert_eq!(
+        Arc::try_unwrap(lock).unwrap().into_inner(),
+        THREADS * ROUNDS
+    );
+}